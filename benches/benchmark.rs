@@ -20,11 +20,12 @@ pub fn 读取(name: &str) -> 默认上下文 {
                 encodables: Some(PathBuf::from(elements)),
                 key_distribution: None,
                 pair_equivalence: None,
+                time_model: None,
             },
             threads: 1,
         },
     };
-    let 输入 = 从命令行参数创建(&参数);
+    let 输入 = 从命令行参数创建(&参数).expect("Failed to read input");
     默认上下文::新建(输入).expect("Failed to create context")
 }
 
@@ -50,11 +51,7 @@ fn 四码定长字词(b: &mut Criterion) {
 
 fn 四码定长单字(b: &mut Criterion) {
     let mut 上下文 = 读取("米十五笔");
-    上下文.词列表 = 上下文
-        .词列表
-        .into_iter()
-        .filter(|x| x.词.chars().count() == 1)
-        .collect();
+    上下文.词列表.retain(|x| x.词.chars().count() == 1);
     上下文
         .配置
         .optimization
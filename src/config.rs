@@ -163,7 +163,7 @@ pub struct 退化配置 {
 // config.analysis end
 
 // config.algebra begin
-type 拼写运算自定义 = IndexMap<String, Vec<运算规则>>;
+pub type 拼写运算自定义 = IndexMap<String, Vec<运算规则>>;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -183,6 +183,26 @@ pub struct 键盘配置 {
     pub mapping_space: Option<IndexMap<String, Vec<安排描述>>>,
     pub mapping_variables: Option<IndexMap<String, 变量规则>>,
     pub mapping_generators: Option<Vec<决策生成器规则>>,
+    pub mapping_affinities: Option<Vec<元素亲和>>,
+    // 有多种拆分的词，以及优化得到的拆分选择
+    pub decomposition_space: Option<IndexMap<String, Vec<拆分描述>>>,
+    pub decompositions: Option<IndexMap<String, String>>,
+}
+
+/// 两个元素之间的亲和关系：两个元素都被选取，且它们的第一码是否相同与 same_key（默认为 true）一致时，在复杂度中加上 score。score 为负数时即为奖励
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct 元素亲和 {
+    pub elements: [String; 2],
+    pub same_key: Option<bool>,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct 拆分描述 {
+    pub value: String,
+    #[serde(default)]
+    pub score: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -249,6 +269,30 @@ pub struct 编码配置 {
     pub short_code_list: Option<Vec<优先简码>>,
     // 组装器
     pub assembler: Option<String>,
+    // 并击
+    pub chord: Option<并击配置>,
+    // 候选排序
+    pub candidate_order: Option<候选排序>,
+}
+
+/// 同一编码上的候选按什么顺序排列
+///
+/// - frequency：按频率从高到低排列
+/// - original：按词信息文件中的顺序排列
+/// - pinned：有固定排名的词按固定排名排在前面，其余的词按频率排列
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum 候选排序 {
+    #[default]
+    Frequency,
+    Original,
+    Pinned,
+}
+
+/// 并击的按键同时按下，没有先后顺序，所以并击方案不能使用 fingering、typing_time、alternation 和 extended_pair_equivalence 这些依赖按键顺序的指标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct 并击配置 {
+    pub size: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -307,7 +351,12 @@ pub enum 简码规则 {
 #[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct 简码模式 {
-    pub prefix: usize,
+    // prefix、positions 和 formula 三者只能给出一个
+    pub prefix: Option<usize>,
+    // 从 0 开始的取码位置，负数表示从后往前数
+    pub positions: Option<Vec<isize>>,
+    // 取码公式，a 到 m 依次表示第 1 到第 13 码，z 到 n 依次表示倒数第 1 到第 13 码
+    pub formula: Option<String>,
     pub count: Option<usize>,
     pub select_keys: Option<Vec<char>>,
 }
@@ -336,25 +385,57 @@ pub struct 码长权重 {
 #[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct 层级权重 {
+    /// 按频率排列的前若干个词
     pub top: Option<usize>,
+    /// 按频率排列、累计频率达到总频率的这一比例所需的词
+    pub coverage: Option<f64>,
+    /// 所有字都在这个字符集文件中的词，文件路径相对于配置文件所在的目录
+    pub charset: Option<String>,
     pub duplication: Option<f64>,
     pub levels: Option<Vec<码长权重>>,
     pub fingering: Option<指法权重>,
+    pub pair_equivalence: Option<f64>,
+    pub key_distribution: Option<f64>,
+    pub finger_distribution: Option<f64>,
+    pub alternation: Option<f64>,
+    pub home_row: Option<f64>,
 }
 
 // let types = ["同手", "大跨", "小跨", "干扰", "错手", "三连", "备用", "备用"];
 pub type 指法权重 = [Option<f64>; 8];
 
 #[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct 部分权重 {
     pub tiers: Option<Vec<层级权重>>,
     pub duplication: Option<f64>,
     pub key_distribution: Option<f64>,
+    pub key_distribution_loss: Option<分布损失>,
+    pub finger_distribution: Option<f64>,
+    pub hand_distribution: Option<f64>,
     pub pair_equivalence: Option<f64>,
     pub extended_pair_equivalence: Option<f64>,
     pub fingering: Option<指法权重>,
     pub levels: Option<Vec<码长权重>>,
+    pub selection: Option<f64>,
+    pub typing_time: Option<f64>,
+    /// 左右手交替的组合占所有组合的比例，越高越好，所以一般给出负的权重
+    pub alternation: Option<f64>,
+    /// 落在基准行上的按键占所有按键的比例，同样一般给出负的权重
+    pub home_row: Option<f64>,
+}
+
+/// 实际分布偏离理想分布的度量方式，默认为 l1
+///
+/// l1 和 squared 分别对每个按键的偏差取绝对值和平方，并按偏差的方向乘以低于惩罚或高于惩罚后求和；max 取其中的最大值；kl 为理想分布相对于实际分布的 KL 散度，不使用惩罚值
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum 分布损失 {
+    #[default]
+    L1,
+    Squared,
+    Kl,
+    Max,
 }
 
 #[skip_serializing_none]
@@ -365,6 +446,51 @@ pub struct 目标配置 {
     pub characters_short: Option<部分权重>,
     pub words_short: Option<部分权重>,
     pub regularization_strength: Option<f64>,
+    /// 十个手指的理想用指分布，手指的编号与键盘布局中的相同，会按总和归一化；不给出时由各个按键的理想值汇总得到
+    pub finger_targets: Option<Vec<f64>>,
+    /// 左右手的理想分布，同样按总和归一化；不给出时由各个按键的理想值汇总得到
+    pub hand_targets: Option<Vec<f64>>,
+    /// 用指标表示的目标函数表达式，给出时代替按权重加权求和得到的目标函数，语法见 objectives/expression.rs
+    pub expression: Option<String>,
+    /// 每个选择键的选择代价，即用这个选择键上屏一个词需要额外付出的按键数；没有给出的选择键中，首选键的代价为 0，其余为 1
+    pub selection_costs: Option<IndexMap<char, f64>>,
+    pub learnability: Option<易学性配置>,
+    pub constraints: Option<Vec<约束配置>>,
+    pub constraint_mode: Option<约束模式>,
+    pub constraint_penalty: Option<f64>,
+}
+
+/// 易学性指标的配置，key_spread、variant_mismatch 和 key_entropy 分别为各类元素占用的键数之和、变体元素与基础元素不同键的比例，以及元素在各个键上分布的熵的权重
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct 易学性配置 {
+    /// 元素的分类，键为类别名称，值为这一类中的元素；不给出时所有元素视为一类
+    pub classes: Option<IndexMap<String, Vec<String>>>,
+    /// 变体元素到基础元素的映射，决策空间中可以归并到某个元素或者引用了某个元素的元素也会自动视为它的变体
+    pub variants: Option<IndexMap<String, String>>,
+    pub key_spread: Option<f64>,
+    pub variant_mismatch: Option<f64>,
+    pub key_entropy: Option<f64>,
+}
+
+/// 对某个指标的硬约束，metric 的写法与目标函数表达式相同，但引用的指标不需要配置权重
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct 约束配置 {
+    pub metric: String,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+/// 处理约束的方式，默认为 lexicographic
+///
+/// lexicographic 先比较约束的违反量，再比较目标函数，违反量不同时退火按违反量之差决定是否接受，不与目标函数换算；penalty 把违反量乘以惩罚系数加到目标函数上，惩罚系数从 constraint_penalty 开始，根据最近的解是否可行自动调整
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum 约束模式 {
+    #[default]
+    Lexicographic,
+    Penalty,
 }
 
 #[skip_serializing_none]
@@ -380,6 +506,72 @@ pub enum 求解器配置 {
 pub struct 优化配置 {
     pub objective: 目标配置,
     pub metaheuristic: Option<求解器配置>,
+    pub keyboard: Option<键盘布局配置>,
+    pub fingering_categories: Option<Vec<指法类别配置>>,
+}
+
+/// 物理键盘的布局，用于指法分析
+///
+/// rows 从上到下给出每一行的按键，fingers 与之一一对应，给出每个按键所用的手指：0 到 4 依次为左手的小指、无名指、中指、食指、拇指，5 到 9 依次为右手的拇指、食指、中指、无名指、小指。home_row 为基准行的序号，从 0 开始。offsets 为每一行的第一个按键向右错开的距离，以键宽为单位，不给出时各行对齐
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct 键盘布局配置 {
+    pub rows: Vec<String>,
+    pub fingers: Vec<String>,
+    pub home_row: Option<usize>,
+    pub offsets: Option<Vec<f64>>,
+}
+
+impl Default for 键盘布局配置 {
+    fn default() -> Self {
+        Self {
+            rows: vec![
+                "1234567890-=".to_string(),
+                "qwertyuiop[]".to_string(),
+                "asdfghjkl;'".to_string(),
+                "zxcvbnm,./".to_string(),
+            ],
+            fingers: vec![
+                "012336678999".to_string(),
+                "012336678999".to_string(),
+                "01233667899".to_string(),
+                "0123366789".to_string(),
+            ],
+            home_row: Some(2),
+            offsets: Some(vec![0.0, 0.5, 0.75, 1.25]),
+        }
+    }
+}
+
+/// 用户自定义的指法类别，依次占用指法向量中的两个备用位置，最多两个
+///
+/// ngrams 直接列出属于该类别的按键组合（两个或三个按键）；predicate 则根据键盘布局中手指和行的关系来描述类别。length 为 3 时，三个按键中相邻的两对都满足 predicate 才算作一次
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct 指法类别配置 {
+    pub name: String,
+    pub ngrams: Option<Vec<String>>,
+    pub predicate: Option<指法谓词>,
+    pub length: Option<usize>,
+}
+
+/// 对一对按键的条件，所有给出的条件都满足时成立
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct 指法谓词 {
+    pub same_hand: Option<bool>,
+    pub same_finger: Option<bool>,
+    pub same_key: Option<bool>,
+    pub direction: Option<滚动方向>,
+    pub max_row_difference: Option<usize>,
+}
+
+/// 同手不同指时的击键方向，inward 为从小指向拇指方向，outward 反之
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum 滚动方向 {
+    Inward,
+    Outward,
 }
 // config.optimization end
 
@@ -454,6 +646,9 @@ impl Default for 配置 {
                 mapping_space: None,
                 mapping_variables: None,
                 mapping_generators: None,
+                mapping_affinities: None,
+                decomposition_space: None,
+                decompositions: None,
             },
             encoder: 编码配置 {
                 max_length: 1,
@@ -466,6 +661,8 @@ impl Default for 配置 {
                 short_code: None,
                 short_code_list: None,
                 assembler: None,
+                chord: None,
+                candidate_order: None,
             },
             optimization: None,
             diagram: None,
@@ -1,21 +1,22 @@
 //! 数据结构的定义
 
-use crate::config::{安排, 广义码位, 简码模式, 简码规则, 安排描述, 配置};
+use crate::config::{候选排序, 安排, 广义码位, 简码模式, 简码规则, 安排描述, 层级权重, 部分权重, 配置};
 use crate::contexts::{
-    上下文, 合并初始决策, 展开变量, 应用生成器, 拓扑排序, 条件, 条件安排
+    上下文, 原始元素图, 合并初始决策, 展开变量, 应用生成器, 拓扑排序, 拼写运算器, 条件, 条件安排,
 };
 use crate::encoders::default::简码数量;
 use crate::interfaces::默认输入;
+use crate::objectives::metric::{自定义指法类别, 键盘几何};
 use crate::optimizers::决策;
 use crate::{
-    元素, 元素图, 可编码对象, 当量信息, 最大按键组合长度, 最大词长, 棱镜, 码表项, 编码, 编码信息,
-    键, 键位分布信息,
+    元素, 元素图, 元素序列, 可编码对象, 当量信息, 最大按键组合长度, 最大词长, 棱镜, 码表项, 编码, 编码信息,
+    键, 键位分布信息, 原始时间模型,
 };
 use crate::{最大元素编码长度, 错误};
 use indexmap::IndexMap;
 use itertools::Itertools;
 use regex::Regex;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde_yaml::to_string;
 
 /// 将用户提供的输入转换为内部数据结构，并提供了一些实用的方法
@@ -30,6 +31,37 @@ pub struct 默认上下文 {
     pub 棱镜: 棱镜,
     pub 选择键: Vec<键>,
     pub 元素图: 元素图,
+    pub 键盘几何: 键盘几何,
+    pub 自定义指法: Vec<自定义指法类别>,
+    /// 各个分组中每个层级包含的词，按一字、多字和全码、简码排列，与目标函数中的计数桶一致
+    pub 层级范围: [[Vec<层级范围>; 2]; 2],
+    pub 时间模型: Option<原始时间模型>,
+}
+
+/// 层级包含的词，用词在所属分组（一字或多字）中按频率排列的序号表示
+#[derive(Debug, Clone)]
+pub enum 层级范围 {
+    /// 序号小于给定值的词
+    前缀(usize),
+    /// 序号对应的值为 true 的词
+    集合(Vec<bool>),
+}
+
+impl 层级范围 {
+    #[inline(always)]
+    pub fn 包含(&self, 序号: usize) -> bool {
+        match self {
+            层级范围::前缀(数量) => 序号 < *数量,
+            层级范围::集合(集合) => 集合[序号],
+        }
+    }
+
+    pub fn 大小(&self) -> usize {
+        match self {
+            层级范围::前缀(数量) => *数量,
+            层级范围::集合(集合) => 集合.iter().filter(|x| **x).count(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -125,6 +157,7 @@ type 默认条件安排 = 条件安排<默认安排>;
 #[derive(Debug, Clone)]
 pub struct 默认决策 {
     pub 元素: Vec<默认安排>,
+    pub 拆分: Vec<usize>, // 每个有多种拆分的词选用的拆分序号
 }
 
 impl 默认决策 {
@@ -134,7 +167,7 @@ impl 默认决策 {
                 return false;
             }
         }
-        return true;
+        true
     }
 }
 
@@ -154,6 +187,16 @@ impl 决策 for 默认决策 {
 #[derive(Debug, Clone)]
 pub struct 默认决策空间 {
     pub 元素: Vec<Vec<默认条件安排>>,
+    pub 拆分: Vec<候选拆分>,
+}
+
+/// 一个有多种拆分的词，词序号是它在词列表中的位置
+#[derive(Debug, Clone)]
+pub struct 候选拆分 {
+    pub 词序号: usize,
+    pub 序列: Vec<元素序列>,
+    pub 原始序列: Vec<String>,
+    pub 分数: Vec<f64>,
 }
 
 impl 上下文 for 默认上下文 {
@@ -165,20 +208,52 @@ impl 上下文 for 默认上下文 {
             let 新安排 = 决策.元素[元素].to(&self.棱镜);
             *安排 = 新安排;
         }
+        if !self.决策空间.拆分.is_empty() {
+            let mut 拆分 = IndexMap::new();
+            for (候选拆分, 序号) in self.决策空间.拆分.iter().zip(&决策.拆分) {
+                let 词 = self.词列表[候选拆分.词序号].词.clone();
+                拆分.insert(词, 候选拆分.原始序列[*序号].clone());
+            }
+            新配置.form.decompositions = Some(拆分);
+        }
         to_string(&新配置).unwrap()
     }
 }
 
 impl 默认上下文 {
     pub fn 新建(输入: 默认输入) -> Result<Self, 错误> {
-        let (初始决策, 决策空间, 元素图, 选择键, 棱镜) =
+        let (mut 初始决策, mut 决策空间, 元素图, 选择键, 棱镜) =
             Self::构建棱镜和初始决策(&输入.配置)?;
         let 最大码长 = 输入.配置.encoder.max_length;
-        let 词列表 = 棱镜.预处理词列表(输入.词列表, 最大码长)?;
+        let 拼写运算器 = match &输入.配置.algebra {
+            Some(algebra) => 拼写运算器::新建(algebra)?,
+            None => 拼写运算器::default(),
+        };
+        let mut 词列表 = 棱镜.预处理词列表(输入.词列表, 最大码长, &拼写运算器)?;
+        (初始决策.拆分, 决策空间.拆分) =
+            Self::构建拆分空间(&输入.配置, &棱镜, &mut 词列表, &拼写运算器)?;
         let 组合长度 = 最大码长.min(最大按键组合长度);
         let 编码空间大小 = 棱镜.进制.pow(组合长度 as u32) as usize;
         let 键位分布信息 = 棱镜.预处理键位分布信息(&输入.原始键位分布信息);
-        let 当量信息 = 棱镜.预处理当量信息(&输入.原始当量信息, 编码空间大小);
+        let 当量信息 = if let Some(并击配置) = &输入.配置.encoder.chord {
+            if 并击配置.size == 0 || 并击配置.size > 最大按键组合长度 {
+                return Err(format!("并击长度必须在 1 到 {最大按键组合长度} 之间").into());
+            }
+            棱镜.预处理并击当量信息(&输入.原始当量信息, 并击配置.size)
+        } else {
+            棱镜.预处理当量信息(&输入.原始当量信息, 编码空间大小)
+        };
+        let 优化 = 输入.配置.optimization.as_ref();
+        let 键盘布局 = 优化.and_then(|x| x.keyboard.as_ref());
+        let 键盘几何 = match 键盘布局 {
+            Some(键盘布局) => 键盘几何::新建(键盘布局)?,
+            None => 键盘几何::default(),
+        };
+        let 自定义指法 = match 优化 {
+            Some(优化) => 自定义指法类别::构建列表(&优化.fingering_categories, &键盘几何)?,
+            None => vec![],
+        };
+        let 层级范围 = Self::解析层级范围(&输入.配置, &词列表, &输入.字符集)?;
         Ok(Self {
             配置: 输入.配置,
             词列表,
@@ -189,9 +264,105 @@ impl 默认上下文 {
             选择键,
             决策空间,
             元素图,
+            键盘几何,
+            自定义指法,
+            层级范围,
+            时间模型: 输入.原始时间模型,
         })
     }
 
+    /// 把目标函数中用 top、coverage 或 charset 定义的层级统一转换为层级范围，这样计算指标时只需要查表
+    fn 解析层级范围(
+        配置: &配置,
+        词列表: &[可编码对象],
+        字符集: &FxHashMap<String, String>,
+    ) -> Result<[[Vec<层级范围>; 2]; 2], 错误> {
+        let mut 结果: [[Vec<层级范围>; 2]; 2] = Default::default();
+        let Some(优化) = &配置.optimization else {
+            return Ok(结果);
+        };
+        let 目标 = &优化.objective;
+        let 分组列表 = [
+            [&目标.characters_full, &目标.characters_short],
+            [&目标.words_full, &目标.words_short],
+        ];
+        for (桶索引, 分组) in 分组列表.iter().enumerate() {
+            let 桶: Vec<_> = 词列表
+                .iter()
+                .filter(|x| (x.词长 == 1) == (桶索引 == 0))
+                .collect();
+            for (全简索引, 部分权重) in 分组.iter().enumerate() {
+                let Some(部分权重 {
+                    tiers: Some(层级列表),
+                    ..
+                }) = 部分权重
+                else {
+                    continue;
+                };
+                for 层级 in 层级列表 {
+                    let 范围 = Self::解析层级(层级, &桶, 词列表.len(), 字符集)?;
+                    结果[桶索引][全简索引].push(范围);
+                }
+            }
+        }
+        Ok(结果)
+    }
+
+    fn 解析层级(
+        层级: &层级权重,
+        桶: &[&可编码对象],
+        总词数: usize,
+        字符集: &FxHashMap<String, String>,
+    ) -> Result<层级范围, 错误> {
+        // 容错码排在桶的末尾，不属于任何层级
+        let 主词数 = 桶.iter().filter(|x| !x.容错).count();
+        match (层级.top, 层级.coverage, &层级.charset) {
+            (top, None, None) => {
+                let 数量 = top.unwrap_or(总词数);
+                if 主词数 < 桶.len() {
+                    Ok(层级范围::前缀(数量.min(主词数)))
+                } else {
+                    Ok(层级范围::前缀(数量))
+                }
+            }
+            (None, Some(coverage), None) => {
+                if !(0.0..=1.0).contains(&coverage) {
+                    return Err(format!("层级的覆盖率 {coverage} 不在 0 到 1 之间").into());
+                }
+                // 覆盖率按照词的完整频率计算，也就是主编码和容错码分得的频率之和
+                let mut 词频率: FxHashMap<usize, u64> = FxHashMap::default();
+                for 词 in 桶 {
+                    *词频率.entry(词.原始顺序).or_default() += 词.频率;
+                }
+                let 总频率: u64 = 词频率.values().sum();
+                let 目标频率 = coverage * 总频率 as f64;
+                let mut 累计频率 = 0;
+                let mut 数量 = 0;
+                for 词 in &桶[..主词数] {
+                    if 累计频率 as f64 >= 目标频率 {
+                        break;
+                    }
+                    累计频率 += 词频率[&词.原始顺序];
+                    数量 += 1;
+                }
+                Ok(层级范围::前缀(数量))
+            }
+            (None, None, Some(charset)) => {
+                let 内容 = 字符集
+                    .get(charset)
+                    .ok_or(format!("没有提供字符集文件 {charset} 的内容"))?;
+                let 字符: FxHashSet<char> =
+                    内容.chars().filter(|x| !x.is_whitespace()).collect();
+                let 集合 = 桶
+                    .iter()
+                    .map(|x| !x.容错 && x.词.chars().all(|c| 字符.contains(&c)))
+                    .collect();
+                Ok(层级范围::集合(集合))
+            }
+            _ => Err("层级只能用 top、coverage 和 charset 中的一种方式定义".into()),
+        }
+    }
+
     pub fn 构建棱镜和初始决策(
         配置: &配置,
     ) -> Result<(默认决策, 默认决策空间, 元素图, Vec<键>, 棱镜), 错误> {
@@ -254,16 +425,88 @@ impl 默认上下文 {
         Ok((初始决策, 决策空间, 元素图, 选择键, 棱镜))
     }
 
+    /// 将配置中的拆分空间对应到词列表中的词上，并且用初始的拆分选择替换词列表中的元素序列
+    ///
+    /// 如果配置中已经有了优化得到的拆分选择，就以它为初始选择；否则选用和词信息中相同的拆分，都没有时选用第一个拆分
+    pub fn 构建拆分空间(
+        配置: &配置,
+        棱镜: &棱镜,
+        词列表: &mut [可编码对象],
+        拼写运算器: &拼写运算器,
+    ) -> Result<(Vec<usize>, Vec<候选拆分>), 错误> {
+        let mut 初始拆分 = vec![];
+        let mut 拆分空间 = vec![];
+        let Some(原始拆分空间) = &配置.form.decomposition_space else {
+            return Ok((初始拆分, 拆分空间));
+        };
+        let 已选拆分 = 配置.form.decompositions.clone().unwrap_or_default();
+        let 最大码长 = 配置.encoder.max_length;
+        let mut 词序号表: FxHashMap<&str, usize> = FxHashMap::default();
+        for (词序号, 词) in 词列表.iter().enumerate() {
+            if !词.容错 {
+                词序号表.insert(&词.词, 词序号);
+            }
+        }
+        let mut 初始序列 = vec![];
+        for (词, 拆分描述列表) in 原始拆分空间 {
+            let Some(词序号) = 词序号表.get(词.as_str()) else {
+                return Err(format!("拆分空间中的词「{词}」不在词列表中").into());
+            };
+            if 拆分描述列表.is_empty() {
+                return Err(format!("拆分空间中的词「{词}」没有可选的拆分").into());
+            }
+            let mut 候选拆分 = 候选拆分 {
+                词序号: *词序号,
+                序列: vec![],
+                原始序列: vec![],
+                分数: vec![],
+            };
+            for 拆分描述 in 拆分描述列表 {
+                let 序列 = 棱镜.解析元素序列(词, &拆分描述.value, 最大码长, 拼写运算器)?;
+                候选拆分.序列.push(序列);
+                候选拆分.原始序列.push(拆分描述.value.clone());
+                候选拆分.分数.push(拆分描述.score);
+            }
+            let 序号 = if let Some(已选) = 已选拆分.get(词) {
+                候选拆分
+                    .原始序列
+                    .iter()
+                    .position(|x| x == 已选)
+                    .ok_or(format!("词「{词}」选用的拆分「{已选}」不在拆分空间中"))?
+            } else {
+                let 当前序列 = &词列表[*词序号].元素序列;
+                候选拆分
+                    .序列
+                    .iter()
+                    .position(|x| x == 当前序列)
+                    .unwrap_or(0)
+            };
+            初始序列.push((*词序号, 候选拆分.序列[序号].clone()));
+            初始拆分.push(序号);
+            拆分空间.push(候选拆分);
+        }
+        for (词序号, 序列) in 初始序列 {
+            词列表[词序号].元素序列 = 序列;
+        }
+        Ok((初始拆分, 拆分空间))
+    }
+
     pub fn 构建初始决策和决策空间(
         棱镜: &棱镜,
         排序后元素名称: &Vec<String>,
         原始决策: &IndexMap<String, 安排>,
         原始决策空间: &IndexMap<String, Vec<安排描述>>,
-        原始元素图: &FxHashMap<String, Vec<String>>,
+        原始元素图: &原始元素图,
     ) -> Result<(默认决策, 默认决策空间, 元素图), 错误> {
         // 3. 使用棱镜构建初始决策和决策空间
-        let mut 初始决策 = 默认决策 { 元素: vec![] };
-        let mut 决策空间 = 默认决策空间 { 元素: vec![] };
+        let mut 初始决策 = 默认决策 {
+            元素: vec![],
+            拆分: vec![],
+        };
+        let mut 决策空间 = 默认决策空间 {
+            元素: vec![],
+            拆分: vec![],
+        };
         let mut 元素图: FxHashMap<元素, Vec<_>> = FxHashMap::default();
         for k in 0..棱镜.进制 {
             let 安排 = 默认安排::键位([(k as usize, 0), (0, 0), (0, 0), (0, 0)]);
@@ -280,7 +523,7 @@ impl 默认上下文 {
             let mut 安排列表 = vec![];
             let 原始安排列表 = 原始决策空间[元素名称].clone();
             let 编号 = 棱镜.元素转数字[元素名称];
-            let 安排 = 默认安排::from(原始安排, &棱镜, 元素名称)?;
+            let 安排 = 默认安排::from(原始安排, 棱镜, 元素名称)?;
             for 其余原始安排 in &原始安排列表 {
                 let mut 条件列表 = vec![];
                 for c in 其余原始安排.condition.clone().unwrap_or_default() {
@@ -288,12 +531,12 @@ impl 默认上下文 {
                         条件列表.push(条件 {
                             元素: *条件元素,
                             谓词: c.op == "是",
-                            值: 默认安排::from(&c.value, &棱镜, &c.element)?,
+                            值: 默认安排::from(&c.value, 棱镜, &c.element)?,
                         });
                     }
                 }
                 let 条件字根安排 = 默认条件安排 {
-                    安排: 默认安排::from(&其余原始安排.value, &棱镜, 元素名称)?,
+                    安排: 默认安排::from(&其余原始安排.value, 棱镜, 元素名称)?,
                     条件: 条件列表,
                     分数: 其余原始安排.score,
                 };
@@ -308,8 +551,37 @@ impl 默认上下文 {
         Ok((初始决策, 决策空间, 元素图))
     }
 
+    /// 计算每个词的候选次序，同一编码上次序小的词排在前面
+    pub fn 计算候选次序(&self) -> Vec<usize> {
+        let mut 词序号列表: Vec<_> = (0..self.词列表.len()).collect();
+        // 词列表已经按照频率排好序，而且排序是稳定的，所以其余的词仍然按照频率排列
+        match self.配置.encoder.candidate_order.unwrap_or_default() {
+            候选排序::Frequency => {}
+            候选排序::Original => {
+                词序号列表.sort_by_key(|x| (self.词列表[*x].容错, self.词列表[*x].原始顺序))
+            }
+            候选排序::Pinned => {
+                词序号列表.sort_by_key(|x| self.词列表[*x].固定排名.unwrap_or(u64::MAX))
+            }
+        }
+        let mut 候选次序 = vec![0; 词序号列表.len()];
+        for (次序, 词序号) in 词序号列表.into_iter().enumerate() {
+            候选次序[词序号] = 次序;
+        }
+        候选次序
+    }
+
+    /// 按照决策中选择的拆分得到每个词当前的元素序列，没有候选拆分的词沿用词列表中的元素序列
+    pub fn 当前元素序列(&self, 决策: &默认决策) -> Vec<&元素序列> {
+        let mut 序列列表: Vec<_> = self.词列表.iter().map(|x| &x.元素序列).collect();
+        for (候选拆分, 序号) in self.决策空间.拆分.iter().zip(&决策.拆分) {
+            序列列表[候选拆分.词序号] = &候选拆分.序列[*序号];
+        }
+        序列列表
+    }
+
     pub fn 生成码表(&self, 编码结果: &[编码信息]) -> Vec<码表项> {
-        let mut 码表: Vec<(usize, 码表项)> = Vec::new();
+        let mut 码表: Vec<((usize, bool), 码表项)> = Vec::new();
         let 转编码 = |code: 编码| self.棱镜.数字转编码(code).iter().collect();
         for (序号, 可编码对象) in self.词列表.iter().enumerate() {
             let 码表项 = 码表项 {
@@ -319,7 +591,7 @@ impl 默认上下文 {
                 简码: 转编码(编码结果[序号].简码.原始编码),
                 简码排名: 编码结果[序号].简码.原始编码候选位置,
             };
-            码表.push((可编码对象.原始顺序, 码表项));
+            码表.push(((可编码对象.原始顺序, 可编码对象.容错), 码表项));
         }
         码表.sort_by_key(|x| x.0);
         码表.into_iter().map(|x| x.1).collect()
@@ -361,7 +633,36 @@ impl 默认上下文 {
     ) -> Result<Vec<简码数量>, 错误> {
         let mut compiled_schemes = Vec::new();
         for scheme in schemes {
-            let prefix = scheme.prefix;
+            let 最大码长 = self.配置.encoder.max_length;
+            let 取码方式 = (&scheme.prefix, &scheme.positions, &scheme.formula);
+            let (prefix, positions) = match 取码方式 {
+                (Some(prefix), None, None) => (*prefix, None),
+                (None, Some(positions), None) => (positions.len(), Some(positions.clone())),
+                (None, None, Some(formula)) => {
+                    let mut positions = vec![];
+                    for c in formula.chars() {
+                        let position = match c {
+                            'a'..='m' => c as isize - 'a' as isize,
+                            'n'..='z' => c as isize - 'z' as isize - 1,
+                            _ => {
+                                return Err(
+                                    format!("简码公式「{formula}」包含非法字符「{c}」").into()
+                                );
+                            }
+                        };
+                        positions.push(position);
+                    }
+                    (positions.len(), Some(positions))
+                }
+                _ => {
+                    return Err("简码模式中 prefix、positions 和 formula 必须给出且只能给出一个".into());
+                }
+            };
+            if prefix == 0 || prefix > 最大码长 {
+                return Err(
+                    format!("简码长度 {prefix} 不在 1 到最大码长 {最大码长} 之间").into(),
+                );
+            }
             let count = scheme.count.unwrap_or(1);
             let select_keys = if let Some(keys) = &scheme.select_keys {
                 let mut transformed_keys = Vec::new();
@@ -382,6 +683,7 @@ impl 默认上下文 {
             }
             compiled_schemes.push(简码数量 {
                 prefix,
+                positions,
                 select_keys: select_keys[..count].to_vec(),
             });
         }
@@ -419,3 +721,119 @@ impl 默认上下文 {
         self.棱镜.进制.pow(max_length as u32) as usize
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::原始可编码对象;
+
+    const 测试配置: &str = r#"
+form:
+  alphabet: abc
+  mapping: {口: a, 日: b, 木: c}
+encoder:
+  max_length: 2
+  candidate_order: original
+optimization:
+  objective:
+    characters_full:
+      duplication: 1
+      tiers: [{duplication: 1}, {top: 10, duplication: 1}, {coverage: 0.45, duplication: 1}]
+"#;
+
+    fn 新建上下文(词列表: &[(&str, &str, u64)]) -> Result<默认上下文, 错误> {
+        let 词列表 = 词列表
+            .iter()
+            .map(|(词, 元素序列, 频率)| 原始可编码对象 {
+                词: 词.to_string(),
+                元素序列: 元素序列.to_string(),
+                频率: *频率,
+                简码长度: u64::MAX,
+                固定排名: None,
+            })
+            .collect();
+        默认上下文::新建(默认输入 {
+            配置: serde_yaml::from_str(测试配置).unwrap(),
+            词列表,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_weighted_alternate_sequences() {
+        let 上下文 = 新建上下文(&[
+            ("一", "口 日:1|日 口:1", 400),
+            ("二", "木 口", 300),
+            ("三", "日 木", 150),
+        ])
+        .unwrap();
+        let 词列表: Vec<_> = 上下文
+            .词列表
+            .iter()
+            .map(|x| (x.词.as_str(), x.容错, x.频率))
+            .collect();
+        // 主编码按照词的完整频率排序，容错码排在最后
+        assert_eq!(
+            词列表,
+            [
+                ("一", false, 200),
+                ("二", false, 300),
+                ("三", false, 150),
+                ("一", true, 200)
+            ]
+        );
+        let 口 = 上下文.棱镜.元素转数字["口"];
+        assert_eq!(上下文.词列表[3].元素序列[1].0, 口);
+        assert_eq!(上下文.计算候选次序(), [0, 1, 2, 3]);
+        // 容错码不计入层级，覆盖率也按照完整频率计算
+        let 层级范围 = &上下文.层级范围[0][0];
+        assert_eq!(层级范围[0].大小(), 3);
+        assert!(!层级范围[1].包含(3));
+        assert_eq!(层级范围[2].大小(), 1);
+        let 上下文 = 新建上下文(&[("一", "口 日:3|日 口", 400)]).unwrap();
+        let 频率: Vec<_> = 上下文.词列表.iter().map(|x| x.频率).collect();
+        assert_eq!(频率, [300, 100]);
+        for 错误序列 in ["口 日:0|日 口", "口 日|日 口:-1", "口 日:x|日 口"] {
+            assert!(
+                新建上下文(&[("一", 错误序列, 400)]).is_err(),
+                "{}",
+                错误序列
+            );
+        }
+    }
+
+    #[test]
+    fn test_pinned_candidate_order() {
+        use crate::encoders::{default::默认编码器, 编码器};
+
+        let mut 配置: 配置 = serde_yaml::from_str(测试配置).unwrap();
+        配置.encoder.candidate_order = Some(候选排序::Pinned);
+        let 词列表 = [("一", 100, None), ("二", 1, Some(5)), ("三", 5, Some(3)), ("四", 50, None)]
+            .iter()
+            .map(|(词, 频率, 固定排名)| 原始可编码对象 {
+                词: 词.to_string(),
+                元素序列: "口 日".to_string(),
+                频率: *频率,
+                简码长度: u64::MAX,
+                固定排名: *固定排名,
+            })
+            .collect();
+        let 上下文 = 默认上下文::新建(默认输入 {
+            配置,
+            词列表,
+            ..Default::default()
+        })
+        .unwrap();
+        // 词列表按频率排列为一、四、三、二；有固定排名的三和二不论频率多低都排在前面，
+        // 它们之间按固定排名排列，固定排名只决定相对顺序，不是绝对的候选位置；
+        // 其余的词按频率排列
+        let 词序: Vec<_> = 上下文.词列表.iter().map(|x| x.词.as_str()).collect();
+        assert_eq!(词序, ["一", "四", "三", "二"]);
+        assert_eq!(上下文.计算候选次序(), [2, 3, 0, 1]);
+        let mut 编码器 = 默认编码器::新建(&上下文).unwrap();
+        let mut 编码结果: Vec<_> = 上下文.词列表.iter().map(编码信息::new).collect();
+        编码器.编码(&上下文.初始决策, &None, &mut 编码结果);
+        let 候选位置: Vec<_> = 编码结果.iter().map(|x| x.全码.原始编码候选位置).collect();
+        assert_eq!(候选位置, [2, 3, 0, 1]);
+    }
+}
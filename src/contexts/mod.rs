@@ -1,5 +1,7 @@
 use crate::{
-    config::{决策生成器规则, 变量规则, 安排, 安排描述, 广义码位},
+    config::{
+        决策生成器规则, 变量规则, 安排, 安排描述, 广义码位, 拼写运算自定义, 运算规则
+    },
     optimizers::决策,
     元素, 错误,
 };
@@ -47,7 +49,7 @@ pub fn 合并初始决策(
     // 确保每个元素的当前决策都在决策空间中
     for (元素名称, 原始安排列表) in 原始决策空间.iter_mut() {
         let 原始安排 = 原始决策[元素名称].clone();
-        if !原始安排列表.iter().any(|x| &x.value == &原始安排) {
+        if !原始安排列表.iter().any(|x| x.value == 原始安排) {
             原始安排列表.insert(
                 0,
                 安排描述 {
@@ -98,9 +100,12 @@ pub fn 展开变量(
     }
 }
 
+/// 拓扑排序的结果：排序后的元素名称，以及从依赖元素指向下游元素的图
+pub type 原始元素图 = FxHashMap<String, Vec<String>>;
+
 pub fn 拓扑排序(
     原始决策空间: &IndexMap<String, Vec<安排描述>>,
-) -> Result<(Vec<String>, FxHashMap<String, Vec<String>>), 错误> {
+) -> Result<(Vec<String>, 原始元素图), 错误> {
     // 构造入度表
     let mut 入度 = FxHashMap::default();
     let mut 元素图 = FxHashMap::default();
@@ -127,10 +132,10 @@ pub fn 拓扑排序(
             }
         }
         for 依赖元素 in &依赖 {
-            元素图.get_mut(依赖元素).map(|v| {
+            if let Some(v) = 元素图.get_mut(依赖元素) {
                 v.push(元素名称.clone());
                 *入度.get_mut(元素名称).unwrap() += 1;
-            });
+            }
         }
     }
 
@@ -224,3 +229,151 @@ pub fn 应用生成器(
         }
     }
 }
+
+/// 编译后的单条拼写运算，语义与 Rime 的 xform 和 xlit 相同
+#[derive(Debug, Clone)]
+pub enum 拼写运算 {
+    变换(Regex, String),
+    转写(FxHashMap<char, char>),
+}
+
+/// 配置中 algebra 字段编译得到的拼写运算集合
+///
+/// 可编码对象中形如「规则名-音节」的元素，如果规则名是 algebra 中的一个键，那么音节部分会依次经过这个规则中的所有运算，改写为「规则名-结果」之后再到键盘映射中查找
+#[derive(Debug, Clone, Default)]
+pub struct 拼写运算器 {
+    pub 规则: FxHashMap<String, Vec<拼写运算>>,
+}
+
+impl 拼写运算器 {
+    pub fn 新建(原始拼写运算: &拼写运算自定义) -> Result<Self, 错误> {
+        // Rime 的替换串中 $1 后面可以直接跟字母，而 regex 会把 $1a 当成名为 1a 的分组，所以统一改写为 ${1}
+        let 分组引用 = Regex::new(r"\$(\d+)").unwrap();
+        let mut 规则 = FxHashMap::default();
+        for (名称, 运算列表) in 原始拼写运算 {
+            let mut 编译后 = vec![];
+            for 运算 in 运算列表 {
+                match 运算 {
+                    运算规则::Xform { from, to } => {
+                        let regex = Regex::new(from).map_err(|e| {
+                            format!("拼写运算「{名称}」中的正则表达式「{from}」不合法：{e}")
+                        })?;
+                        let to = 分组引用.replace_all(to, "$${$1}").to_string();
+                        编译后.push(拼写运算::变换(regex, to));
+                    }
+                    运算规则::Xlit { from, to } => {
+                        let from: Vec<_> = from.chars().collect();
+                        let to: Vec<_> = to.chars().collect();
+                        if from.len() != to.len() {
+                            return Err(format!(
+                                "拼写运算「{名称}」中的转写规则两侧字符数量不一致"
+                            )
+                            .into());
+                        }
+                        编译后.push(拼写运算::转写(from.into_iter().zip(to).collect()));
+                    }
+                }
+            }
+            规则.insert(名称.clone(), 编译后);
+        }
+        Ok(Self { 规则 })
+    }
+
+    /// 对音节依次应用名为「名称」的规则中的所有运算，规则不存在时返回 None
+    pub fn 运算(&self, 名称: &str, 音节: &str) -> Option<String> {
+        let 运算列表 = self.规则.get(名称)?;
+        let mut 结果 = 音节.to_string();
+        for 运算 in 运算列表 {
+            结果 = match 运算 {
+                拼写运算::变换(regex, to) => {
+                    regex.replace_all(&结果, to.as_str()).to_string()
+                }
+                拼写运算::转写(表) => {
+                    结果.chars().map(|c| *表.get(&c).unwrap_or(&c)).collect()
+                }
+            };
+        }
+        Some(结果)
+    }
+
+    pub fn 改写元素名称(&self, 元素名称: &str) -> String {
+        if let Some((名称, 音节)) = 元素名称.split_once('-') {
+            if let Some(结果) = self.运算(名称, 音节) {
+                return format!("{名称}-{结果}");
+            }
+        }
+        元素名称.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contexts::default::默认上下文;
+    use crate::interfaces::默认输入;
+    use crate::原始可编码对象;
+
+    const 测试配置: &str = r#"
+form:
+  alphabet: abcd
+  mapping: {拼-ba: a, 拼-pa: b, 拼-ma: c, 拼-xa: d}
+encoder:
+  max_length: 2
+algebra:
+  拼:
+    - {type: xform, from: '^(\w)(\w+)$', to: '$1x$2'}
+    - {type: xform, from: '^(\w)x', to: '$1'}
+    - {type: xlit, from: bp, to: pb}
+"#;
+
+    fn 新建运算器() -> 拼写运算器 {
+        let 配置: crate::config::配置 = serde_yaml::from_str(测试配置).unwrap();
+        拼写运算器::新建(配置.algebra.as_ref().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_spelling_algebra() {
+        let 运算器 = 新建运算器();
+        let 拼 = &运算器.规则["拼"];
+        // $1 后面直接跟字母时也按照第一个分组处理
+        let 拼写运算::变换(_, to) = &拼[0] else {
+            panic!("第一条运算应为变换");
+        };
+        assert_eq!(to, "${1}x${2}");
+        assert_eq!(运算器.运算("拼", "ma"), Some("ma".to_string()));
+        assert_eq!(运算器.运算("拼", "ba"), Some("pa".to_string()));
+        assert_eq!(运算器.运算("拼", "pa"), Some("ba".to_string()));
+        assert_eq!(运算器.运算("音", "ba"), None);
+        assert_eq!(运算器.改写元素名称("拼-ba"), "拼-pa");
+        assert_eq!(运算器.改写元素名称("音-ba"), "音-ba");
+        assert_eq!(运算器.改写元素名称("口"), "口");
+        let mut 原始拼写运算 = 拼写运算自定义::new();
+        原始拼写运算.insert(
+            "拼".to_string(),
+            vec![运算规则::Xlit {
+                from: "ab".to_string(),
+                to: "a".to_string(),
+            }],
+        );
+        assert!(拼写运算器::新建(&原始拼写运算).is_err());
+    }
+
+    #[test]
+    fn test_spelling_algebra_missing_element() {
+        let 词列表 = vec![原始可编码对象 {
+            词: "一".to_string(),
+            元素序列: "拼-bo".to_string(),
+            频率: 1,
+            简码长度: u64::MAX,
+            固定排名: None,
+        }];
+        let 结果 = 默认上下文::新建(默认输入 {
+            配置: serde_yaml::from_str(测试配置).unwrap(),
+            词列表,
+            ..Default::default()
+        });
+        // 报错中应包含改写之后的名称，便于排查拼写运算的问题
+        let 报错 = 结果.err().unwrap().message;
+        assert!(报错.contains("拼-po"), "{}", 报错);
+    }
+}
@@ -0,0 +1,397 @@
+//! 并击编码器
+//!
+//! 并击方案中，每个码位不再是单个按键，而是同时按下的一组按键。元素序列按照配置中的并击长度切分成若干并击，每个并击内部的按键与顺序无关，因此在编码之前先排序、去重，使得按键相同的并击总是得到相同的编码。
+//!
+//! 与默认编码器相同，全码的候选位置用占用表增量更新；但并击编码器只生成全码，不支持简码，配置了简码时会报错。
+
+use super::default::{
+    占用表, 拆分状态, 线性化, 线性化决策, 编码空间, 编码配置
+};
+use super::编码器;
+use crate::contexts::default::{默认上下文, 默认决策};
+use crate::{
+    元素, 可编码对象, 棱镜, 编码, 编码信息, 部分编码信息, 错误, 键
+};
+use rustc_hash::FxHashMap;
+use std::iter::zip;
+
+pub struct 并击编码器 {
+    棱镜: 棱镜,
+    编码配置: 编码配置,
+    并击长度: usize,
+    乘数列表: Vec<u64>,
+    词信息: Vec<可编码对象>,
+    全码空间: 编码空间,
+    全码占用: 占用表,
+    包含元素的词: Vec<Vec<usize>>,
+    拆分状态: 拆分状态,
+    拆分变化的词: Vec<usize>,
+    候选次序: Vec<usize>,
+    次序到词: Vec<usize>,
+    全码变化: Vec<(usize, 编码, 编码)>,
+}
+
+impl 并击编码器 {
+    pub fn 新建(上下文: &默认上下文) -> Result<Self, 错误> {
+        let 并击配置 = 上下文
+            .配置
+            .encoder
+            .chord
+            .as_ref()
+            .ok_or("并击编码器需要配置 encoder.chord")?;
+        let 并击长度 = 并击配置.size;
+        if 并击长度 == 0 {
+            return Err("并击长度必须大于 0".into());
+        }
+        let 编码配置 = 编码配置::new(上下文)?;
+        if 编码配置.简码配置列表.is_some() {
+            return Err("并击编码器暂不支持简码".into());
+        }
+        // 每个并击都占据固定的位数，不足的部分补零，所以总位数可能超过最大码长
+        let 并击数 = 编码配置.最大码长.div_ceil(并击长度);
+        let 总位数 = 并击数 * 并击长度;
+        if 总位数 >= 8 {
+            return Err("目前暂不支持补齐后码长大于等于 8 的并击方案计算！".into());
+        }
+        let 进制 = 上下文.棱镜.进制;
+        let 乘数列表 = (0..=总位数).map(|x| 进制.pow(x as u32)).collect();
+        let 词信息 = 上下文.词列表.clone();
+        let 线性表长度 = 进制.pow(编码配置.最大码长 as u32) as usize;
+        let 全码空间 = 编码空间 {
+            线性表: vec![u8::default(); 线性表长度],
+            线性表长度,
+            哈希表: FxHashMap::default(),
+        };
+        let mut 包含元素的词 = vec![vec![]; 上下文.棱镜.元素转数字.len() + 1];
+        for (词序号, 词) in 词信息.iter().enumerate() {
+            for (元素, _) in &词.元素序列 {
+                包含元素的词[*元素].push(词序号);
+            }
+        }
+        let 候选次序 = 上下文.计算候选次序();
+        let mut 次序到词 = vec![0; 候选次序.len()];
+        for (词序号, 次序) in 候选次序.iter().enumerate() {
+            次序到词[*次序] = 词序号;
+        }
+        Ok(Self {
+            棱镜: 上下文.棱镜.clone(),
+            编码配置,
+            并击长度,
+            乘数列表,
+            词信息,
+            全码空间,
+            全码占用: 占用表::default(),
+            包含元素的词,
+            拆分状态: 拆分状态::新建(上下文),
+            拆分变化的词: vec![],
+            候选次序,
+            次序到词,
+            全码变化: vec![],
+        })
+    }
+
+    /// 逐个并击排序去重后拼接成原始编码，每个并击占据的位数相同
+    fn 计算原始编码(&self, 映射: &线性化决策, 词: &可编码对象) -> 编码 {
+        let mut 原始编码 = 0;
+        let mut 位数 = 0;
+        for 并击 in 词.元素序列.chunks(self.并击长度) {
+            // 补齐后的总位数小于 8，所以并击长度也小于 8
+            let mut 按键: [键; 8] = [0; 8];
+            let mut 数量 = 0;
+            for (元素, 位置) in 并击 {
+                let 键 = 映射[*元素][*位置];
+                if 键 != 0 {
+                    按键[数量] = 键;
+                    数量 += 1;
+                }
+            }
+            let 按键 = &mut 按键[..数量];
+            按键.sort_unstable();
+            let mut 偏移 = 位数;
+            let mut 上一个键 = 0;
+            for 键 in 按键.iter() {
+                if *键 == 上一个键 {
+                    continue;
+                }
+                原始编码 += 键 * self.乘数列表[偏移];
+                偏移 += 1;
+                上一个键 = *键;
+            }
+            位数 += self.并击长度;
+        }
+        原始编码
+    }
+
+    fn 重置(&mut self) {
+        self.全码空间.线性表.iter_mut().for_each(|x| *x = 0);
+        self.全码空间.哈希表.clear();
+        self.全码占用.清空();
+    }
+
+    /// 与默认编码器相同，全码的候选位置就是词在同一编码的占用者中的位置；补齐后的位数总是并击长度的整数倍
+    fn 刷新全码(
+        &self, 词序号: usize, 全码信息: &mut 部分编码信息, 原始编码候选位置: u8
+    ) {
+        let 位数 = self.词信息[词序号].元素序列.len().div_ceil(self.并击长度) * self.并击长度;
+        let 乘数 = self.乘数列表[位数];
+        let 编码 = self.编码配置.生成编码(全码信息.原始编码, 0, 乘数);
+        全码信息.更新(编码, 原始编码候选位置 > 0, 原始编码候选位置);
+    }
+
+    fn 输出全码(
+        &mut self,
+        映射: &线性化决策,
+        移动的元素: &Option<Vec<元素>>,
+        编码结果: &mut [编码信息],
+    ) {
+        let Some(移动的元素) = 移动的元素 else {
+            self.重置();
+            for (词, 编码信息) in zip(&self.词信息, 编码结果.iter_mut()) {
+                编码信息.全码.原始编码 = self.计算原始编码(映射, 词);
+            }
+            for (次序, 词序号) in self.次序到词.iter().enumerate() {
+                let 全码信息 = &mut 编码结果[*词序号].全码;
+                let 原始编码候选位置 = self.全码空间.查找数量(全码信息.原始编码);
+                self.全码空间.添加(全码信息.原始编码);
+                self.全码占用.添加(全码信息.原始编码, 次序);
+                self.刷新全码(*词序号, 全码信息, 原始编码候选位置);
+            }
+            return;
+        };
+        let 包含元素的词 = &self.包含元素的词;
+        let 受影响的词 = 移动的元素
+            .iter()
+            .flat_map(|元素| &包含元素的词[*元素])
+            .chain(&self.拆分变化的词);
+        for 索引 in 受影响的词 {
+            let 全码信息 = &mut 编码结果[*索引].全码;
+            let 原始编码 = self.计算原始编码(映射, &self.词信息[*索引]);
+            if 全码信息.原始编码 != 原始编码 {
+                self.全码变化.push((*索引, 全码信息.原始编码, 原始编码));
+            }
+            全码信息.原始编码 = 原始编码;
+        }
+        // 只有变化前后的编码上的占用者需要更新候选位置
+        for (词序号, 原编码, 新编码) in &self.全码变化 {
+            let 次序 = self.候选次序[*词序号];
+            self.全码占用.移除(*原编码, 次序);
+            self.全码占用.添加(*新编码, 次序);
+            self.全码空间.设置(*原编码, self.全码占用.数量(*原编码));
+            self.全码空间.设置(*新编码, self.全码占用.数量(*新编码));
+        }
+        for (_, 原编码, 新编码) in &self.全码变化 {
+            for 编码 in [原编码, 新编码] {
+                for (位置, 次序) in self.全码占用.全部(*编码).iter().enumerate() {
+                    let 位置 = 位置.min(u8::MAX as usize) as u8;
+                    let 词序号 = self.次序到词[*次序];
+                    self.刷新全码(词序号, &mut 编码结果[词序号].全码, 位置);
+                }
+            }
+        }
+        self.全码变化.clear();
+    }
+}
+
+impl 编码器 for 并击编码器 {
+    type 决策 = 默认决策;
+    fn 编码(
+        &mut self, 决策: &Self::决策, 移动的元素: &Option<Vec<元素>>, 输出: &mut [编码信息]
+    ) {
+        self.拆分变化的词.clear();
+        self.拆分状态.同步(
+            决策,
+            &mut self.词信息,
+            &mut self.包含元素的词,
+            &mut self.拆分变化的词,
+        );
+        let 线性化决策 = 线性化(决策, &self.棱镜);
+        self.输出全码(&线性化决策, 移动的元素, 输出);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::配置;
+    use crate::interfaces::默认输入;
+    use crate::objectives::default::默认目标函数;
+    use crate::objectives::目标函数;
+    use crate::operators::default::测试工具::{伪随机, 逐步变异};
+    use crate::{原始可编码对象, 原始当量信息};
+
+    const 测试配置: &str = r#"
+form:
+  alphabet: abcdefgh
+  mapping: {口: a, 日: b, 木: c, 水: d}
+  mapping_space:
+    口: [{value: a, score: 0}, {value: e, score: 0}, {value: b, score: 0}]
+    日: [{value: b, score: 0}, {value: f, score: 0}, {value: a, score: 0}]
+    木: [{value: c, score: 0}, {value: g, score: 0}]
+    水: [{value: d, score: 0}, {value: h, score: 0}]
+encoder:
+  max_length: 4
+  select_keys: ["_", ";"]
+  chord: {size: 2}
+optimization:
+  objective:
+    characters_full: {pair_equivalence: 1}
+"#;
+
+    fn 词(词: &str, 元素序列: &str, 频率: u64) -> 原始可编码对象 {
+        原始可编码对象 {
+            词: 词.to_string(),
+            元素序列: 元素序列.to_string(),
+            频率,
+            简码长度: u64::MAX,
+            固定排名: None,
+        }
+    }
+
+    #[test]
+    fn test_chord_encoding() {
+        let 原始当量信息: 原始当量信息 = [("ba", 1.5), ("ac", 0.5), ("cb", 0.25)]
+            .iter()
+            .map(|(k, v)| (k.to_string(), *v))
+            .collect();
+        let 上下文 = 默认上下文::新建(默认输入 {
+            配置: serde_yaml::from_str(测试配置).unwrap(),
+            词列表: vec![
+                词("一", "口 日", 4),
+                词("二", "日 口", 3),
+                词("三", "口 口 木", 2),
+                词("四", "口 木 日 木", 1),
+            ],
+            原始当量信息,
+            ..Default::default()
+        })
+        .unwrap();
+        let 键 = |c: char| 上下文.棱镜.键转数字[&c];
+        let 进制 = 上下文.棱镜.进制;
+        let 编码器 = 并击编码器::新建(&上下文).unwrap();
+        let mut 目标函数 = 默认目标函数::新建(&上下文, 编码器).unwrap();
+        目标函数.计算(&上下文.初始决策, &None);
+        let 全码: Vec<_> = 目标函数.编码结果.iter().map(|x| x.全码).collect();
+        // 并击内部排序，所以「口日」和「日口」的编码相同，后出现的要选重
+        let ab = 键('a') + 键('b') * 进制;
+        assert_eq!(全码[0].原始编码, ab);
+        assert_eq!(全码[1].原始编码, ab);
+        assert_eq!(全码[1].原始编码候选位置, 1);
+        assert!(!全码[0].选重标记 && 全码[1].选重标记);
+        // 并击内部去重，不足并击长度的部分补零
+        assert_eq!(全码[2].原始编码, 键('a') + 键('c') * 进制.pow(2));
+        assert_eq!(
+            全码[3].原始编码,
+            键('a') + 键('c') * 进制 + (键('b') + 键('c') * 进制) * 进制.pow(2)
+        );
+        // 并击的当量与按键顺序无关，多个并击的当量相加
+        let 参数 = &目标函数.参数;
+        assert_eq!(参数.当量信息[(键('b') + 键('a') * 进制) as usize], 1.5);
+        assert_eq!(参数.编码当量(全码[0].原始编码), 1.5);
+        assert_eq!(参数.编码当量(全码[2].原始编码), 0.0);
+        assert_eq!(参数.编码当量(全码[3].原始编码), 0.75);
+    }
+
+    #[test]
+    fn test_chord_size_limit() {
+        let mut 配置: 配置 = serde_yaml::from_str(测试配置).unwrap();
+        配置.encoder.chord.as_mut().unwrap().size = 5;
+        配置.encoder.max_length = 5;
+        let 结果 = 默认上下文::新建(默认输入 {
+            配置,
+            ..Default::default()
+        });
+        assert!(结果.is_err());
+    }
+
+    #[test]
+    fn test_chord_equivalence_is_mean_of_orders() {
+        // ab、ba 和 aab 都是 a、b 的并击，取三者的平均值，与哈希表的遍历顺序无关
+        let 原始当量信息: 原始当量信息 = [("ab", 1.0), ("ba", 2.0), ("aab", 3.0), ("ac", 0.5)]
+            .iter()
+            .map(|(k, v)| (k.to_string(), *v))
+            .collect();
+        let 上下文 = 默认上下文::新建(默认输入 {
+            配置: serde_yaml::from_str(测试配置).unwrap(),
+            词列表: vec![词("一", "口 日", 1)],
+            原始当量信息,
+            ..Default::default()
+        })
+        .unwrap();
+        let 键 = |c: char| 上下文.棱镜.键转数字[&c];
+        let 进制 = 上下文.棱镜.进制;
+        let 编码器 = 并击编码器::新建(&上下文).unwrap();
+        let 目标函数 = 默认目标函数::新建(&上下文, 编码器).unwrap();
+        let 当量信息 = &目标函数.参数.当量信息;
+        assert_eq!(当量信息[(键('a') + 键('b') * 进制) as usize], 2.0);
+        assert_eq!(当量信息[(键('b') + 键('a') * 进制) as usize], 2.0);
+        assert_eq!(当量信息[(键('c') + 键('a') * 进制) as usize], 0.5);
+    }
+
+    #[test]
+    fn test_chord_rejects_ordered_metrics() {
+        let 上下文 = |部分权重: &str| {
+            let mut 配置: 配置 = serde_yaml::from_str(测试配置).unwrap();
+            配置
+                .optimization
+                .as_mut()
+                .unwrap()
+                .objective
+                .characters_full = Some(serde_yaml::from_str(部分权重).unwrap());
+            默认上下文::新建(默认输入 {
+                配置,
+                词列表: vec![词("一", "口 日", 1)],
+                ..Default::default()
+            })
+            .unwrap()
+        };
+        for 部分权重 in [
+            "{extended_pair_equivalence: 1}",
+            "{fingering: [1, null, null, null, null, null, null, null]}",
+            "{typing_time: 1}",
+            "{alternation: -1}",
+            "{tiers: [{top: 10, alternation: -1}]}",
+        ] {
+            let 上下文 = 上下文(部分权重);
+            let 编码器 = 并击编码器::新建(&上下文).unwrap();
+            let 错误 = 默认目标函数::新建(&上下文, 编码器).err().unwrap();
+            assert!(错误.message.starts_with("并击方案不支持"), "{}", 部分权重);
+        }
+        // 基准行和键位分布只看按键本身，可以用于并击方案
+        let 上下文 = 上下文("{home_row: -1, key_distribution: 1, pair_equivalence: 1}");
+        let 编码器 = 并击编码器::新建(&上下文).unwrap();
+        assert!(默认目标函数::新建(&上下文, 编码器).is_ok());
+    }
+
+    #[test]
+    fn test_incremental_chord_encoding() {
+        let 元素 = ["口", "日", "木", "水"];
+        let mut 随机数 = 伪随机(12345);
+        let 词列表: Vec<_> = (0..200)
+            .map(|序号: usize| {
+                let 字 = char::from_u32(0x4e00 + 序号 as u32).unwrap().to_string();
+                词(&字, &随机数.元素序列(&元素, 2, 4), (1000 - 序号) as u64)
+            })
+            .collect();
+        let 上下文 = 默认上下文::新建(默认输入 {
+            配置: serde_yaml::from_str(测试配置).unwrap(),
+            词列表,
+            ..Default::default()
+        })
+        .unwrap();
+        let mut 编码器 = 并击编码器::新建(&上下文).unwrap();
+        let mut 全量编码器 = 并击编码器::新建(&上下文).unwrap();
+        let 初始结果: Vec<_> = 上下文.词列表.iter().map(编码信息::new).collect();
+        let mut 增量结果 = 初始结果.clone();
+        编码器.编码(&上下文.初始决策, &None, &mut 增量结果);
+        逐步变异(&上下文, 200, |_, 尝试决策, 变化| {
+            编码器.编码(尝试决策, &Some(变化), &mut 增量结果);
+            let mut 全量结果 = 初始结果.clone();
+            全量编码器.编码(尝试决策, &None, &mut 全量结果);
+            for (甲, 乙) in zip(&增量结果, &全量结果) {
+                assert_eq!(甲.全码.原始编码, 乙.全码.原始编码);
+                assert_eq!(甲.全码.原始编码候选位置, 乙.全码.原始编码候选位置);
+                assert_eq!(甲.全码.实际编码, 乙.全码.实际编码);
+            }
+        });
+    }
+}
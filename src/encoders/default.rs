@@ -1,8 +1,12 @@
 use super::编码器;
-use crate::contexts::default::{默认上下文, 默认决策, 默认安排};
-use crate::{元素, 可编码对象, 最大词长, 编码, 编码信息, 自动上屏, 键};
+use crate::contexts::default::{候选拆分, 默认上下文, 默认决策, 默认安排};
+use crate::{
+    元素, 可编码对象, 最大词长, 编码, 编码信息, 自动上屏, 部分编码信息, 键
+};
 use crate::{最大元素编码长度, 棱镜, 错误};
 use rustc_hash::FxHashMap;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::iter::zip;
 
 pub type 线性化决策 = Vec<[键; 最大元素编码长度]>;
@@ -28,6 +32,17 @@ impl 编码空间 {
         }
     }
 
+    #[inline(always)]
+    pub fn 设置(&mut self, 编码: u64, 数量: u8) {
+        if 编码 < self.线性表长度 as u64 {
+            self.线性表[编码 as usize] = 数量;
+        } else if 数量 == 0 {
+            self.哈希表.remove(&编码);
+        } else {
+            self.哈希表.insert(编码, 数量);
+        }
+    }
+
     #[inline(always)]
     pub fn 查找数量(&self, 编码: u64) -> u8 {
         if 编码 < self.线性表长度 as u64 {
@@ -38,9 +53,106 @@ impl 编码空间 {
     }
 }
 
+/// 记录每个编码被哪些词占用，词用出码顺序表示并按升序排列
+///
+/// 和编码空间不同，占用表可以查询某个词之前有多少个词占用了同一个编码，所以单个词的编码变化之后不需要重新遍历所有词
+#[derive(Debug, Clone, Default)]
+pub struct 占用表 {
+    pub 表: FxHashMap<编码, Vec<usize>>,
+}
+
+impl 占用表 {
+    pub fn 添加(&mut self, 编码: 编码, 顺序: usize) {
+        let 列表 = self.表.entry(编码).or_default();
+        let 位置 = 列表.partition_point(|x| *x < 顺序);
+        列表.insert(位置, 顺序);
+    }
+
+    pub fn 移除(&mut self, 编码: 编码, 顺序: usize) {
+        if let Some(列表) = self.表.get_mut(&编码) {
+            if let Ok(位置) = 列表.binary_search(&顺序) {
+                列表.remove(位置);
+            }
+        }
+    }
+
+    /// 顺序在给定顺序之前的占用数量，和编码空间一样饱和到 u8 的上限
+    pub fn 之前数量(&self, 编码: 编码, 顺序: usize) -> u8 {
+        let 数量 = self
+            .表
+            .get(&编码)
+            .map_or(0, |列表| 列表.partition_point(|x| *x < 顺序));
+        数量.min(u8::MAX as usize) as u8
+    }
+
+    pub fn 数量(&self, 编码: 编码) -> u8 {
+        let 数量 = self.表.get(&编码).map_or(0, |列表| 列表.len());
+        数量.min(u8::MAX as usize) as u8
+    }
+
+    pub fn 全部(&self, 编码: 编码) -> &[usize] {
+        self.表.get(&编码).map_or(&[], |列表| 列表.as_slice())
+    }
+
+    /// 顺序在给定顺序之后的所有占用
+    pub fn 之后(&self, 编码: 编码, 顺序: usize) -> &[usize] {
+        match self.表.get(&编码) {
+            Some(列表) => &列表[列表.partition_point(|x| *x <= 顺序)..],
+            None => &[],
+        }
+    }
+
+    pub fn 清空(&mut self) {
+        self.表.clear();
+    }
+}
+
+/// 编码器当前使用的拆分，决策中的拆分选择变化时据此更新词信息和包含元素的词
+pub struct 拆分状态 {
+    候选拆分列表: Vec<候选拆分>,
+    当前拆分: Vec<usize>,
+}
+
+impl 拆分状态 {
+    pub fn 新建(上下文: &默认上下文) -> Self {
+        Self {
+            候选拆分列表: 上下文.决策空间.拆分.clone(),
+            当前拆分: 上下文.初始决策.拆分.clone(),
+        }
+    }
+
+    /// 将拆分发生变化的词追加到变化的词中
+    pub fn 同步(
+        &mut self,
+        决策: &默认决策,
+        词信息: &mut [可编码对象],
+        包含元素的词: &mut [Vec<usize>],
+        变化的词: &mut Vec<usize>,
+    ) {
+        for (序号, 候选拆分) in self.候选拆分列表.iter().enumerate() {
+            let 新拆分 = 决策.拆分[序号];
+            if 新拆分 == self.当前拆分[序号] {
+                continue;
+            }
+            let 词序号 = 候选拆分.词序号;
+            for (元素, _) in &词信息[词序号].元素序列 {
+                包含元素的词[*元素].retain(|x| *x != 词序号);
+            }
+            词信息[词序号].元素序列 = 候选拆分.序列[新拆分].clone();
+            for (元素, _) in &词信息[词序号].元素序列 {
+                包含元素的词[*元素].push(词序号);
+            }
+            self.当前拆分[序号] = 新拆分;
+            变化的词.push(词序号);
+        }
+    }
+}
+
+/// 编译后的简码模式，prefix 为简码的长度，给出 positions 时按位置从全码中取码，否则取全码的前 prefix 码
 #[derive(Debug)]
 pub struct 简码数量 {
     pub prefix: usize,
+    pub positions: Option<Vec<isize>>,
     pub select_keys: Vec<键>,
 }
 
@@ -98,6 +210,32 @@ impl 编码配置 {
             .unwrap_or(&self.选择键[0]);
         原始编码 + 选择键 * 选择键乘数
     }
+
+    /// 按照简码模式从全码中取出简码，全码不长于简码或者取码位置超出全码时返回 None
+    #[inline(always)]
+    pub fn 截取简码(&self, 全码: 编码, 简码数量: &简码数量) -> Option<编码> {
+        let 乘数 = self.乘数列表[简码数量.prefix];
+        if 全码 < 乘数 {
+            return None;
+        }
+        let Some(positions) = &简码数量.positions else {
+            return Some(全码 % 乘数);
+        };
+        let 码长 = self.乘数列表.partition_point(|x| *x <= 全码) as isize;
+        let mut 简码 = 0;
+        for (i, position) in positions.iter().enumerate() {
+            let 位置 = if *position < 0 {
+                码长 + position
+            } else {
+                *position
+            };
+            if 位置 < 0 || 位置 >= 码长 {
+                return None;
+            }
+            简码 += 全码 / self.乘数列表[位置 as usize] % self.进制 * self.乘数列表[i];
+        }
+        Some(简码)
+    }
 }
 
 pub struct 默认编码器 {
@@ -105,8 +243,20 @@ pub struct 默认编码器 {
     编码配置: 编码配置,
     词信息: Vec<可编码对象>,
     全码空间: 编码空间,
-    简码空间: 编码空间,
+    全码占用: 占用表,
     包含元素的词: Vec<Vec<usize>>,
+    拆分状态: 拆分状态,
+    拆分变化的词: Vec<usize>,
+    // 简码的增量更新
+    候选次序: Vec<usize>,
+    次序到词: Vec<usize>,
+    出简顺序: Vec<usize>,
+    简码占用: 占用表,
+    简码关注: 占用表,
+    简码关注表: Vec<Vec<编码>>,
+    全码变化: Vec<(usize, 编码, 编码)>,
+    待更新: BinaryHeap<Reverse<usize>>,
+    已加入: Vec<bool>,
 }
 
 impl 默认编码器 {
@@ -126,7 +276,6 @@ impl 默认编码器 {
             线性表长度,
             哈希表: FxHashMap::default(),
         };
-        let 简码空间 = 全码空间.clone();
         let mut 包含元素的词 = vec![];
         for _ in 0..=上下文.棱镜.元素转数字.len() {
             包含元素的词.push(vec![]);
@@ -136,14 +285,43 @@ impl 默认编码器 {
                 包含元素的词[*元素].push(词序号);
             }
         }
+        let 候选次序 = 上下文.计算候选次序();
+        let mut 次序到词 = vec![0; 候选次序.len()];
+        for (词序号, 次序) in 候选次序.iter().enumerate() {
+            次序到词[*次序] = 词序号;
+        }
+        // 优先简码先于常规简码出码，同一类中按照候选次序出码
+        let 词数 = 词信息.len();
+        let 出简顺序 = 词信息
+            .iter()
+            .zip(&候选次序)
+            .map(|(词, 次序)| {
+                if 词.简码长度 == u64::MAX {
+                    词数 + 次序
+                } else {
+                    *次序
+                }
+            })
+            .collect();
         let 编码配置 = 编码配置::new(上下文)?;
         Ok(Self {
             编码配置,
-            词信息,
             全码空间,
-            简码空间,
+            全码占用: 占用表::default(),
             包含元素的词,
+            拆分状态: 拆分状态::新建(上下文),
+            拆分变化的词: vec![],
             棱镜: 上下文.棱镜.clone(),
+            候选次序,
+            次序到词,
+            出简顺序,
+            简码占用: 占用表::default(),
+            简码关注: 占用表::default(),
+            简码关注表: vec![vec![]; 词数],
+            全码变化: vec![],
+            待更新: BinaryHeap::new(),
+            已加入: vec![false; 词数],
+            词信息,
         })
     }
 
@@ -152,10 +330,20 @@ impl 默认编码器 {
             *x = 0;
         });
         self.全码空间.哈希表.clear();
-        self.简码空间.线性表.iter_mut().for_each(|x| {
-            *x = 0;
-        });
-        self.简码空间.哈希表.clear();
+        self.全码占用.清空();
+    }
+
+    /// 全码的候选位置就是词在同一编码的占用者中的位置，占用者按照词序号排列
+    fn 刷新全码(
+        &self, 词序号: usize, 全码信息: &mut 部分编码信息, 原始编码候选位置: u8
+    ) {
+        let 编码配置 = &self.编码配置;
+        // 生成实际编码，并向全码信息中写入实际编码、实际编码是否重码以及候选位置的信息，用于测评
+        // 注意：对于全码来说，暂且忽略次选及之后的选择键的影响，统一视为首选进行编码。这可以避免在四码类方案中大量出现五码的编码，影响性能
+        let 乘数 = 编码配置.乘数列表[self.词信息[词序号].元素序列.len()];
+        let 编码 = 编码配置.生成编码(全码信息.原始编码, 0, 乘数);
+        let 是否重码 = 原始编码候选位置 > 0;
+        全码信息.更新(编码, 是否重码, 原始编码候选位置);
     }
 
     fn 输出全码(
@@ -164,19 +352,46 @@ impl 默认编码器 {
         移动的元素: &Option<Vec<元素>>,
         编码结果: &mut [编码信息],
     ) {
+        if 移动的元素.is_none() {
+            self.重置();
+        }
         let 编码配置 = &self.编码配置;
-        // 根据移动的元素更新编码结果，如果没有移动的元素则直接全部生成
+        // 根据移动的元素和拆分变化的词更新编码结果，如果没有移动的元素则直接全部生成
         if let Some(移动的元素) = 移动的元素 {
-            for 元素 in 移动的元素 {
-                for 索引 in &self.包含元素的词[*元素] {
-                    let 词 = &self.词信息[*索引];
-                    let 全码信息 = &mut 编码结果[*索引].全码;
-                    let mut 原始编码 = 0;
-                    for ((元素, 位置), 乘数) in zip(&词.元素序列, &编码配置.乘数列表)
-                    {
-                        原始编码 += 映射[*元素][*位置] * 乘数;
+            let 包含元素的词 = &self.包含元素的词;
+            let 受影响的词 = 移动的元素
+                .iter()
+                .flat_map(|元素| &包含元素的词[*元素])
+                .chain(&self.拆分变化的词);
+            for 索引 in 受影响的词 {
+                let 词 = &self.词信息[*索引];
+                let 全码信息 = &mut 编码结果[*索引].全码;
+                let mut 原始编码 = 0;
+                for ((元素, 位置), 乘数) in zip(&词.元素序列, &编码配置.乘数列表)
+                {
+                    原始编码 += 映射[*元素][*位置] * 乘数;
+                }
+                if 全码信息.原始编码 != 原始编码 {
+                    self.全码变化.push((*索引, 全码信息.原始编码, 原始编码));
+                }
+                全码信息.原始编码 = 原始编码;
+            }
+            // 只有变化前后的编码上的占用者需要更新候选位置
+            for (词序号, 原编码, 新编码) in &self.全码变化 {
+                let 次序 = self.候选次序[*词序号];
+                self.全码占用.移除(*原编码, 次序);
+                self.全码占用.添加(*新编码, 次序);
+                self.全码空间.设置(*原编码, self.全码占用.数量(*原编码));
+                self.全码空间.设置(*新编码, self.全码占用.数量(*新编码));
+            }
+            for (_, 原编码, 新编码) in &self.全码变化 {
+                for 编码 in [原编码, 新编码] {
+                    let 占用者 = self.全码占用.全部(*编码);
+                    for (位置, 次序) in 占用者.iter().enumerate() {
+                        let 位置 = 位置.min(u8::MAX as usize) as u8;
+                        let 词序号 = self.次序到词[*次序];
+                        self.刷新全码(词序号, &mut 编码结果[词序号].全码, 位置);
                     }
-                    全码信息.原始编码 = 原始编码;
                 }
             }
         } else {
@@ -189,105 +404,165 @@ impl 默认编码器 {
                 }
                 全码信息.原始编码 = 原始编码;
             }
-        }
-
-        for (编码信息, 词) in 编码结果.iter_mut().zip(&self.词信息) {
-            let 全码信息 = &mut 编码信息.全码;
-            // 首先查找编码空间中已经有了多少个同样原始编码的词，确定候选位置
-            let 原始编码候选位置 = self.全码空间.查找数量(全码信息.原始编码);
-            全码信息.原始编码候选位置 = 原始编码候选位置;
-            self.全码空间.添加(全码信息.原始编码);
-            // 然后生成实际编码，并向全码信息中写入实际编码和实际编码是否重码的信息，用于测评
-            // 注意：对于全码来说，暂且忽略次选及之后的选择键的影响，统一视为首选进行编码。这可以避免在四码类方案中大量出现五码的编码，影响性能
-            let 乘数 = 编码配置.乘数列表[词.元素序列.len()];
-            let 编码 = 编码配置.生成编码(全码信息.原始编码, 0, 乘数);
-            let 是否重码 = 原始编码候选位置 > 0;
-            全码信息.更新(编码, 是否重码);
+            // 按照候选次序依次查找编码空间中已经有了多少个同样原始编码的词，确定候选位置
+            for (次序, 词序号) in self.次序到词.iter().enumerate() {
+                let 全码信息 = &mut 编码结果[*词序号].全码;
+                let 原始编码候选位置 = self.全码空间.查找数量(全码信息.原始编码);
+                self.全码空间.添加(全码信息.原始编码);
+                self.全码占用.添加(全码信息.原始编码, 次序);
+                self.刷新全码(*词序号, 全码信息, 原始编码候选位置);
+            }
         }
     }
 
-    fn 输出简码(&mut self, 编码结果: &mut [编码信息]) {
+    /// 按照出码顺序为一个词计算简码，只依赖于出码顺序在它之前的词的简码
+    ///
+    /// 计算过程中查询过的编码都记录在简码关注表中，这些编码上的占用情况发生变化时，需要重新计算这个词
+    fn 计算简码(&mut self, 词序号: usize, 编码结果: &mut 编码信息) {
         let 编码配置 = &self.编码配置;
         let 简码配置列表 = 编码配置.简码配置列表.as_ref().unwrap();
+        let 词 = &self.词信息[词序号];
+        let 顺序 = self.出简顺序[词序号];
+        let 关注 = &mut self.简码关注表[词序号];
+        关注.clear();
         // 优先简码
-        for (词, 编码结果) in zip(&self.词信息, 编码结果.iter_mut()) {
-            if 词.简码长度 == u64::MAX {
-                continue;
-            }
+        if 词.简码长度 != u64::MAX {
             let 原始编码 = 编码结果.全码.原始编码 % 编码配置.乘数列表[词.简码长度 as usize];
             编码结果.简码.原始编码 = 原始编码;
-            let 序号 = self.简码空间.查找数量(原始编码);
+            let 序号 = self.简码占用.之前数量(原始编码, 顺序);
+            关注.push(原始编码);
             let 乘数 = 编码配置.乘数列表[词.简码长度 as usize];
             let 编码 = 编码配置.生成编码(原始编码, 序号, 乘数);
-            编码结果.简码.更新(编码, 序号 > 0);
-            self.简码空间.添加(原始编码);
+            编码结果.简码.更新(编码, 序号 > 0, 序号);
+            return;
         }
-        // 常规简码
-        for (词, 编码结果) in zip(&self.词信息, 编码结果.iter_mut()) {
-            if 词.简码长度 != u64::MAX {
+        // 常规简码，容错码直接使用全码
+        let 简码配置: &[简码数量] = if 词.容错 {
+            &[]
+        } else {
+            &简码配置列表[词.词长 - 1]
+        };
+        let 全码信息 = &编码结果.全码;
+        let 简码信息 = &mut 编码结果.简码;
+        for 出简方式 in 简码配置 {
+            let 简码数量 {
+                prefix,
+                select_keys,
+                ..
+            } = 出简方式;
+            let 乘数 = 编码配置.乘数列表[*prefix];
+            // 将全码截取一部分出来，如果根本没有这么多码，就放弃
+            let Some(原始编码) = 编码配置.截取简码(全码信息.原始编码, 出简方式)
+            else {
+                continue;
+            };
+            // 检查当前简码位置上的候选数量是否达到上限
+            let 序号 = self
+                .全码空间
+                .查找数量(原始编码)
+                .saturating_add(self.简码占用.之前数量(原始编码, 顺序));
+            关注.push(原始编码);
+            if 序号 >= select_keys.len() as u8 {
                 continue;
             }
-            let 简码配置 = &简码配置列表[词.词长 - 1];
-            let mut 有简码 = false;
-            let 全码信息 = &编码结果.全码;
-            let 简码信息 = &mut 编码结果.简码;
-            for 出简方式 in 简码配置 {
-                let 简码数量 {
-                    prefix,
-                    select_keys,
-                } = 出简方式;
-                let 乘数 = 编码配置.乘数列表[*prefix];
-                // 如果根本没有这么多码，就放弃
-                if 全码信息.原始编码 < 乘数 {
-                    continue;
-                }
-                // 将全码截取一部分出来，检查当前简码位置上的候选数量是否达到上限
-                let 原始编码 = 全码信息.原始编码 % 乘数;
-                let 序号 = self.全码空间.查找数量(原始编码) + self.简码空间.查找数量(原始编码);
-                if 序号 >= select_keys.len() as u8 {
-                    continue;
+            // 如果没有达到上限，就可以出这个简码
+            let 编码 = 编码配置.生成编码(原始编码, 序号, 乘数);
+            简码信息.原始编码 = 原始编码;
+            简码信息.更新(编码, false, 序号);
+            return;
+        }
+        let 序号 = self.简码占用.之前数量(全码信息.原始编码, 顺序);
+        关注.push(全码信息.原始编码);
+        简码信息.原始编码 = 全码信息.原始编码;
+        简码信息.更新(全码信息.实际编码, 序号 > 0, 序号);
+    }
+
+    /// 出码顺序对词数取余就是候选次序
+    fn 加入待更新(&mut self, 顺序: usize) {
+        let 词序号 = self.次序到词[顺序 % self.词信息.len()];
+        if !self.已加入[词序号] {
+            self.已加入[词序号] = true;
+            self.待更新.push(Reverse(顺序));
+        }
+    }
+
+    /// 没有变化信息时，所有词都按出码顺序重新计算；否则只重新计算受到影响的词
+    ///
+    /// 受到影响的词包括全码变化了的词，以及关注了这些词变化前后的全码的词。按出码顺序依次处理它们，如果某个词的简码变了，那么出码顺序在它之后、关注了变化前后的简码的词也要重新计算
+    fn 输出简码(
+        &mut self, 移动的元素: &Option<Vec<元素>>, 编码结果: &mut [编码信息]
+    ) {
+        let mut 全码变化 = std::mem::take(&mut self.全码变化);
+        if 移动的元素.is_none() {
+            self.简码占用.清空();
+            self.简码关注.清空();
+            for 顺序 in self.出简顺序.clone() {
+                self.加入待更新(顺序);
+            }
+        } else {
+            for (词序号, 原编码, 新编码) in &全码变化 {
+                self.加入待更新(self.出简顺序[*词序号]);
+                for 编码 in [*原编码, *新编码] {
+                    for 顺序 in self.简码关注.全部(编码).to_vec() {
+                        self.加入待更新(顺序);
+                    }
                 }
-                // 如果没有达到上限，就可以出这个简码
-                let 编码 = 编码配置.生成编码(原始编码, 序号, 乘数);
-                简码信息.原始编码 = 原始编码;
-                简码信息.原始编码候选位置 = 序号;
-                简码信息.更新(编码, false);
-                self.简码空间.添加(原始编码);
-                有简码 = true;
-                break;
             }
-            if !有简码 {
-                let 序号 = self.简码空间.查找数量(全码信息.原始编码);
-                简码信息.原始编码 = 全码信息.原始编码;
-                简码信息.原始编码候选位置 = 序号;
-                简码信息.更新(全码信息.实际编码, 序号 > 0);
-                self.简码空间.添加(全码信息.原始编码);
+        }
+        // 保留已分配的空间
+        全码变化.clear();
+        self.全码变化 = 全码变化;
+        while let Some(Reverse(顺序)) = self.待更新.pop() {
+            let 词序号 = self.次序到词[顺序 % self.词信息.len()];
+            self.已加入[词序号] = false;
+            let 原编码 = 编码结果[词序号].简码.原始编码;
+            self.简码占用.移除(原编码, 顺序);
+            for 编码 in &self.简码关注表[词序号] {
+                self.简码关注.移除(*编码, 顺序);
+            }
+            self.计算简码(词序号, &mut 编码结果[词序号]);
+            let 新编码 = 编码结果[词序号].简码.原始编码;
+            self.简码占用.添加(新编码, 顺序);
+            for 编码 in &self.简码关注表[词序号] {
+                self.简码关注.添加(*编码, 顺序);
+            }
+            if 新编码 != 原编码 {
+                for 编码 in [原编码, 新编码] {
+                    for 后续顺序 in self.简码关注.之后(编码, 顺序).to_vec() {
+                        self.加入待更新(后续顺序);
+                    }
+                }
             }
         }
     }
 
     pub fn 线性化(&self, 决策: &默认决策, 棱镜: &棱镜) -> 线性化决策 {
-        let mut result: 线性化决策 = vec![Default::default(); 决策.元素.len()];
-        for (序号, 安排) in 决策.元素.iter().enumerate() {
-            if 序号 < 棱镜.进制 as usize {
-                result[序号] = [序号 as 键, 0, 0, 0];
+        线性化(决策, 棱镜)
+    }
+}
+
+/// 将决策中的引用和归并全部展开，得到每个元素的每个码位对应的键
+pub fn 线性化(决策: &默认决策, 棱镜: &棱镜) -> 线性化决策 {
+    let mut result: 线性化决策 = vec![Default::default(); 决策.元素.len()];
+    for (序号, 安排) in 决策.元素.iter().enumerate() {
+        if 序号 < 棱镜.进制 as usize {
+            result[序号] = [序号 as 键, 0, 0, 0];
+            continue;
+        }
+        match 安排 {
+            默认安排::归并(元素) => {
+                result[序号] = result[*元素];
                 continue;
             }
-            match 安排 {
-                默认安排::归并(元素) => {
-                    result[序号] = result[*元素];
-                    continue;
-                }
-                默认安排::键位(列表) => {
-                    for (i, (元素, 位置)) in 列表.iter().enumerate() {
-                        result[序号][i] = result[*元素][*位置];
-                    }
+            默认安排::键位(列表) => {
+                for (i, (元素, 位置)) in 列表.iter().enumerate() {
+                    result[序号][i] = result[*元素][*位置];
                 }
-                _ => {}
             }
+            _ => {}
         }
-        result
     }
+    result
 }
 
 impl 编码器 for 默认编码器 {
@@ -295,14 +570,167 @@ impl 编码器 for 默认编码器 {
     fn 编码(
         &mut self, 映射: &Self::决策, 移动的元素: &Option<Vec<元素>>, 输出: &mut [编码信息]
     ) {
-        self.重置();
+        self.拆分变化的词.clear();
+        self.拆分状态.同步(
+            映射,
+            &mut self.词信息,
+            &mut self.包含元素的词,
+            &mut self.拆分变化的词,
+        );
         let 线性化决策 = self.线性化(映射, &self.棱镜);
         self.输出全码(&线性化决策, 移动的元素, 输出);
         if self.编码配置.简码配置列表.is_none()
             || self.编码配置.简码配置列表.as_ref().unwrap().is_empty()
         {
+            self.全码变化.clear();
             return;
         }
-        self.输出简码(输出);
+        self.输出简码(移动的元素, 输出);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{候选排序, 拆分描述, 配置};
+    use crate::interfaces::默认输入;
+    use crate::operators::default::测试工具::{伪随机, 逐步变异};
+    use crate::原始可编码对象;
+    use indexmap::IndexMap;
+
+    const 测试配置: &str = r#"
+form:
+  alphabet: abcdefghijklmnopqrstuvwxyz
+  mapping: {口: a, 日: b, 木: c, 水: d, 火: e, 土: f, 金: g, 人: h}
+  mapping_space:
+    口: [{value: a, score: 0}, {value: i, score: 0}, {value: b, score: 0}]
+    日: [{value: b, score: 0}, {value: j, score: 0}, {value: a, score: 0}]
+    木: [{value: c, score: 0}, {value: k, score: 0}]
+    水: [{value: d, score: 0}, {value: l, score: 0}]
+    火: [{value: e, score: 0}, {value: m, score: 0}]
+    土: [{value: f, score: 0}, {value: n, score: 0}]
+    金: [{value: g, score: 0}, {value: o, score: 0}]
+    人: [{value: h, score: 0}, {value: p, score: 0}]
+encoder:
+  max_length: 4
+  select_keys: ["_", ";"]
+  auto_select_length: 4
+  short_code:
+    - length_equal: 1
+      schemes:
+        - prefix: 1
+        - prefix: 2
+          count: 2
+    - length_in_range: [2, 10]
+      schemes:
+        - formula: az
+        - positions: [0, 1, -1]
+"#;
+
+    fn 比较(甲: &部分编码信息, 乙: &部分编码信息) -> bool {
+        甲.原始编码 == 乙.原始编码
+            && 甲.原始编码候选位置 == 乙.原始编码候选位置
+            && 甲.实际编码 == 乙.实际编码
+            && 甲.选重标记 == 乙.选重标记
+    }
+
+    #[test]
+    fn test_incremental_encoding() {
+        // 用固定的伪随机序列生成词表，让不同的词在全码和简码上都有足够多的冲突
+        let 元素 = ["口", "日", "木", "水", "火", "土", "金", "人"];
+        let mut 随机数 = 伪随机(12345);
+        let mut 词列表 = vec![];
+        let mut 拆分空间 = IndexMap::new();
+        for 序号 in 0..400 {
+            let 字 = char::from_u32(0x4e00 + 序号).unwrap().to_string();
+            let 词 = if 序号 < 300 { 字 } else { 字.repeat(2) };
+            let 元素序列 = 随机数.元素序列(&元素, 2, 4);
+            // 一部分词有三种拆分，其中第一种和词表中的拆分相同
+            if 序号.is_multiple_of(10) {
+                let 拆分列表: Vec<_> = (0..3)
+                    .map(|分数| 拆分描述 {
+                        value: if 分数 == 0 {
+                            元素序列.clone()
+                        } else {
+                            随机数.元素序列(&元素, 2, 4)
+                        },
+                        score: 分数 as f64,
+                    })
+                    .collect();
+                拆分空间.insert(词.clone(), 拆分列表);
+            }
+            let 简码长度 = if 随机数.下一个().is_multiple_of(20) {
+                1 + 随机数.下一个() as u64 % 2
+            } else {
+                u64::MAX
+            };
+            let 固定排名 = if 随机数.下一个().is_multiple_of(20) {
+                Some(随机数.下一个() as u64 % 3)
+            } else {
+                None
+            };
+            词列表.push(原始可编码对象 {
+                词,
+                元素序列,
+                频率: (1000 - 序号) as u64,
+                简码长度,
+                固定排名,
+            });
+        }
+        let mut 配置: 配置 = serde_yaml::from_str(测试配置).unwrap();
+        配置.form.decomposition_space = Some(拆分空间);
+        配置.encoder.candidate_order = Some(候选排序::Pinned);
+        let 上下文 = 默认上下文::新建(默认输入 {
+            配置,
+            词列表,
+            ..Default::default()
+        })
+        .unwrap();
+        let mut 编码器 = 默认编码器::新建(&上下文).unwrap();
+        let mut 全量编码器 = 默认编码器::新建(&上下文).unwrap();
+        let 初始结果: Vec<_> = 上下文.词列表.iter().map(编码信息::new).collect();
+        let mut 增量结果 = 初始结果.clone();
+        编码器.编码(&上下文.初始决策, &None, &mut 增量结果);
+        逐步变异(&上下文, 200, |_, 尝试决策, 变化| {
+            编码器.编码(尝试决策, &Some(变化), &mut 增量结果);
+            let mut 全量结果 = 初始结果.clone();
+            全量编码器.编码(尝试决策, &None, &mut 全量结果);
+            for (甲, 乙) in zip(&增量结果, &全量结果) {
+                assert!(比较(&甲.全码, &乙.全码));
+                assert!(比较(&甲.简码, &乙.简码));
+            }
+        });
+    }
+
+    #[test]
+    fn test_short_code_positions() {
+        let 上下文 = 默认上下文::新建(默认输入 {
+            配置: serde_yaml::from_str(测试配置).unwrap(),
+            ..Default::default()
+        })
+        .unwrap();
+        let 编码配置 = 编码配置::new(&上下文).unwrap();
+        let 转换 = |编码: &str| {
+            编码
+                .chars()
+                .rev()
+                .fold(0, |结果, c| 结果 * 编码配置.进制 + 上下文.棱镜.键转数字[&c])
+        };
+        let 简码模式 = &编码配置.简码配置列表.as_ref().unwrap()[1];
+        assert_eq!(
+            编码配置.截取简码(转换("abcd"), &简码模式[0]),
+            Some(转换("ad"))
+        );
+        assert_eq!(
+            编码配置.截取简码(转换("abc"), &简码模式[0]),
+            Some(转换("ac"))
+        );
+        assert_eq!(
+            编码配置.截取简码(转换("abcd"), &简码模式[1]),
+            Some(转换("abd"))
+        );
+        assert_eq!(编码配置.截取简码(转换("abc"), &简码模式[1]), None);
+        let 前缀 = &编码配置.简码配置列表.as_ref().unwrap()[0][1];
+        assert_eq!(编码配置.截取简码(转换("abcd"), 前缀), Some(转换("ab")));
     }
 }
@@ -2,6 +2,7 @@
 
 use crate::{optimizers::决策, 编码信息};
 
+pub mod chord;
 pub mod default;
 
 pub trait 编码器 {
@@ -1,9 +1,14 @@
 use crate::config::配置;
 use crate::interfaces::{消息, 界面, 默认输入};
-use crate::{原始可编码对象, 原始当量信息, 原始键位分布信息, 码表项};
+use crate::objectives::analysis::贡献分析;
+use crate::objectives::corpus::{分词方法, 语料指标};
+use crate::{
+    原始可编码对象, 原始当量信息, 原始时间模型, 原始键位分布信息, 码表项, 错误
+};
 use chrono::Local;
 use clap::{Parser, Subcommand};
 use csv::{ReaderBuilder, WriterBuilder};
+use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 use std::fs::{create_dir_all, read_to_string, write, OpenOptions};
@@ -48,6 +53,9 @@ pub struct 数据参数 {
     /// 双键速度当量表，默认为 assets 目录下的 equivalence.txt
     #[arg(short, long, value_name = "FILE")]
     pub pair_equivalence: Option<PathBuf>,
+    /// 打字时间模型，使用 typing_time 指标时需要提供
+    #[arg(long, value_name = "FILE")]
+    pub time_model: Option<PathBuf>,
 }
 
 /// 命令行中所有可用的子命令
@@ -66,6 +74,17 @@ pub enum 命令 {
         #[arg(short, long, default_value = "1")]
         threads: usize,
     },
+    #[command(about = "用生成的码表对语料分词，并统计实际击键序列上的各类评测指标")]
+    EvaluateCorpus {
+        #[command(flatten)]
+        data: 数据参数,
+        /// 纯文本语料
+        #[arg(long, value_name = "FILE")]
+        corpus: PathBuf,
+        /// 分词方法
+        #[arg(long, value_enum, default_value = "longest")]
+        segmenter: 分词方法,
+    },
     /// 启动 Web API 服务器
     #[command(about = "启动 HTTP API 服务器")]
     Server {
@@ -138,20 +157,38 @@ impl<P: 命令行参数> 命令行<P> {
         write(&path, metric_str).unwrap();
     }
 
+    pub fn 输出贡献分析(&self, analysis: 贡献分析) {
+        let path = self.输出目录.join("贡献分析.yaml");
+        let analysis_str = serde_yaml::to_string(&analysis).unwrap();
+        write(&path, analysis_str).unwrap();
+        println!("贡献分析保存在 {} 中", path.display());
+    }
+
+    pub fn 输出语料指标(&self, metric: 语料指标) {
+        let path = self.输出目录.join("语料指标.yaml");
+        print!("{metric}");
+        let metric_str = serde_yaml::to_string(&metric).unwrap();
+        write(&path, metric_str).unwrap();
+    }
+
     pub fn 生成子命令行(&self, index: usize) -> 命令行<P> {
         let child_dir = self.输出目录.join(format!("{index}"));
         命令行::新建(self.参数.clone(), Some(child_dir))
     }
 }
 
-pub fn 从命令行参数创建(参数: &默认命令行参数) -> 默认输入 {
-    let (config, encodables, key_distribution, pair_equivalence) = match &参数.command {
-        命令::Encode { data } | 命令::Optimize { data, .. } => (
-            data.config.clone(),
-            data.encodables.clone(),
-            data.key_distribution.clone(),
-            data.pair_equivalence.clone(),
-        ),
+pub fn 从命令行参数创建(参数: &默认命令行参数) -> Result<默认输入, 错误> {
+    let (config, encodables, key_distribution, pair_equivalence, time_model) = match &参数.command
+    {
+        命令::Encode { data } | 命令::Optimize { data, .. } | 命令::EvaluateCorpus { data, .. } => {
+            (
+                data.config.clone(),
+                data.encodables.clone(),
+                data.key_distribution.clone(),
+                data.pair_equivalence.clone(),
+                data.time_model.clone(),
+            )
+        }
         命令::Server { .. } => {
             panic!("Server 命令不需要数据准备");
         }
@@ -167,12 +204,42 @@ pub fn 从命令行参数创建(参数: &默认命令行参数) -> 默认输入
     let key_distribution: 原始键位分布信息 = 读取文本文件(keq_path);
     let peq_path = pair_equivalence.unwrap_or(assets_dir.join("equivalence.txt"));
     let pair_equivalence: 原始当量信息 = 读取文本文件(peq_path);
-    默认输入 {
+    let mut charsets = FxHashMap::default();
+    if let Some(optimization) = &config.optimization {
+        let objective = &optimization.objective;
+        for partial in [
+            &objective.characters_full,
+            &objective.characters_short,
+            &objective.words_full,
+            &objective.words_short,
+        ] {
+            let tiers = partial.iter().flat_map(|x| x.tiers.iter().flatten());
+            // 字符集文件的相对路径以配置文件所在的目录为基准
+            let config_dir = config_path.parent().unwrap_or(Path::new(""));
+            for path in tiers.filter_map(|x| x.charset.as_ref()) {
+                let full_path = config_dir.join(path);
+                let content = read_to_string(&full_path)
+                    .map_err(|e| format!("无法读取字符集文件 {}：{e}", full_path.display()))?;
+                charsets.insert(path.clone(), content);
+            }
+        }
+    }
+    let time_model = match time_model {
+        Some(path) => {
+            let content = read_to_string(&path)
+                .map_err(|e| format!("无法读取时间模型文件 {}：{e}", path.display()))?;
+            Some(原始时间模型::解析(&content)?)
+        }
+        None => None,
+    };
+    Ok(默认输入 {
         配置: config,
         原始键位分布信息: key_distribution,
         原始当量信息: pair_equivalence,
         词列表: encodables,
-    }
+        字符集: charsets,
+        原始时间模型: time_model,
+    })
 }
 
 impl<P: 命令行参数> 界面 for 命令行<P> {
@@ -188,6 +255,13 @@ impl<P: 命令行参数> 界面 for 命令行<P> {
         } else {
             Box::new(std::io::stdout())
         };
+        let 约束说明 = |feasible: bool| {
+            if feasible {
+                ""
+            } else {
+                "（不满足约束）"
+            }
+        };
         let result = match message {
             消息::TrialMax {
                 temperature,
@@ -214,14 +288,17 @@ impl<P: 命令行参数> 界面 for 命令行<P> {
                 steps,
                 temperature,
                 metric,
+                feasible,
             } => writeln!(
                 &mut writer,
-                "已执行 {steps} 步，当前温度为 {temperature:.2e}，当前评测指标如下：\n{metric}",
+                "已执行 {steps} 步，当前温度为 {temperature:.2e}，当前评测指标{}如下：\n{metric}",
+                约束说明(feasible)
             ),
             消息::BetterSolution {
                 metric,
                 config,
                 save,
+                feasible,
             } => {
                 let 时刻 = Local::now();
                 let 时间戳 = 时刻.format("%m-%d+%H_%M_%S_%3f").to_string();
@@ -238,8 +315,9 @@ impl<P: 命令行参数> 界面 for 命令行<P> {
                 }
                 writeln!(
                     &mut writer,
-                    "{} 系统搜索到了一个更好的方案，评测指标如下：\n{}",
+                    "{} 系统搜索到了一个更好的方案{}，评测指标如下：\n{}",
                     时刻.format("%H:%M:%S"),
+                    约束说明(feasible),
                     metric
                 )
             }
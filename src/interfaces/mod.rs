@@ -1,7 +1,21 @@
+use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
-use crate::{config::配置, 原始可编码对象, 原始当量信息, 原始键位分布信息};
+use crate::{
+    config::{优化配置, 目标配置, 配置},
+    contexts::default::{默认上下文, 默认决策},
+    encoders::编码器,
+    objectives::{
+        analysis::{贡献分析, 默认条目数},
+        default::默认目标函数,
+        metric::默认指标,
+        目标函数,
+    },
+    operators::default::默认操作,
+    optimizers::simulated_annealing::退火方法,
+    原始可编码对象, 原始当量信息, 原始时间模型, 原始键位分布信息, 码表项, 错误,
+};
 
 pub mod command_line;
 pub mod web;
@@ -28,11 +42,13 @@ pub enum 消息 {
         steps: usize,
         temperature: f64,
         metric: String,
+        feasible: bool,
     },
     BetterSolution {
         metric: String,
         config: String,
         save: bool,
+        feasible: bool,
     },
     Elapsed {
         time: u64,
@@ -53,4 +69,51 @@ pub struct 默认输入 {
     pub 词列表: Vec<原始可编码对象>,
     pub 原始键位分布信息: 原始键位分布信息,
     pub 原始当量信息: 原始当量信息,
+    /// 层级中 charset 引用的字符集文件的内容，以文件路径为键
+    #[serde(default)]
+    pub 字符集: FxHashMap<String, String>,
+    #[serde(default)]
+    pub 原始时间模型: Option<原始时间模型>,
+}
+
+impl 默认输入 {
+    /// 用图形界面传入的目标函数配置代替原有的目标函数，只用于评测，所以不需要求解器配置；键盘布局和自定义指法类别保持不变
+    pub fn 替换目标函数(&self, 目标函数配置: 目标配置) -> Self {
+        let mut 输入 = self.clone();
+        let 原优化配置 = 输入.配置.optimization.take();
+        输入.配置.optimization = Some(优化配置 {
+            objective: 目标函数配置,
+            metaheuristic: None,
+            keyboard: 原优化配置.as_ref().and_then(|x| x.keyboard.clone()),
+            fingering_categories: 原优化配置.and_then(|x| x.fingering_categories),
+        });
+        输入
+    }
+}
+
+/// 图形界面和 Web API 共用的编码评测，编码器由调用方按照配置选择
+pub(crate) fn 编码评测<E: 编码器<决策 = 默认决策>>(
+    上下文: &默认上下文, 编码器: E
+) -> Result<(Vec<码表项>, 默认指标), 错误> {
+    let mut 目标函数 = 默认目标函数::新建(上下文, 编码器)?;
+    let (mut 指标, _) = 目标函数.计算(&上下文.初始决策, &None);
+    目标函数.附加说明(&mut 指标);
+    Ok((上下文.生成码表(&目标函数.编码结果), 指标))
+}
+
+pub(crate) fn 分析<E: 编码器<决策 = 默认决策>>(
+    上下文: &默认上下文, 编码器: E
+) -> Result<贡献分析, 错误> {
+    let mut 目标函数 = 默认目标函数::新建(上下文, 编码器)?;
+    目标函数.计算(&上下文.初始决策, &None);
+    Ok(贡献分析::新建(上下文, &目标函数, &上下文.初始决策, 默认条目数))
+}
+
+pub(crate) fn 优化<E: 编码器<决策 = 默认决策>>(
+    上下文: &默认上下文, 编码器: E, 退火: &退火方法, 界面: &dyn 界面
+) -> Result<(), 错误> {
+    let mut 目标函数 = 默认目标函数::新建(上下文, 编码器)?;
+    let mut 操作 = 默认操作::新建(上下文)?;
+    退火.优化(&上下文.初始决策, &mut 目标函数, &mut 操作, 上下文, 界面);
+    Ok(())
 }
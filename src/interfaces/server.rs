@@ -1,19 +1,20 @@
 use super::*;
 use crate::{
-    config::{目标配置, 优化配置, 求解器配置},
+    config::{目标配置, 求解器配置},
     contexts::default::默认上下文,
-    encoders::default::默认编码器,
-    objectives::{default::默认目标函数, metric::默认指标, 目标函数},
-    operators::default::默认操作,
+    encoders::{chord::并击编码器, default::默认编码器},
+    objectives::{analysis::贡献分析, metric::默认指标},
     码表项, 错误,
 };
 use console_error_panic_hook::set_once;
 
+type 消息回调 = Box<dyn Fn(&消息) + Send + Sync>;
+
 /// 纯 Rust 的 Web API 接口，与 wasm_bindgen Web 结构一一对应
 #[derive(Default)]
 pub struct WebApi {
     参数: 默认输入,
-    回调: Option<Box<dyn Fn(&消息) + Send + Sync>>,
+    回调: Option<消息回调>,
 }
 
 impl WebApi {
@@ -45,30 +46,36 @@ impl WebApi {
         &self,
         目标函数配置: 目标配置,
     ) -> Result<(Vec<码表项>, 默认指标), 错误> {
-        let mut 输入 = self.参数.clone();
-        输入.配置.optimization = Some(优化配置 {
-            objective: 目标函数配置,
-            metaheuristic: None,
-        });
+        let 输入 = self.参数.替换目标函数(目标函数配置);
         let 上下文 = 默认上下文::新建(输入)?;
-        let 编码器 = 默认编码器::新建(&上下文)?;
-        let mut 目标函数 = 默认目标函数::新建(&上下文, 编码器)?;
-        let (指标, _) = 目标函数.计算(&上下文.初始决策, &None);
-        let 码表 = 上下文.生成码表(&目标函数.编码结果);
+        if 上下文.配置.encoder.chord.is_some() {
+            编码评测(&上下文, 并击编码器::新建(&上下文)?)
+        } else {
+            编码评测(&上下文, 默认编码器::新建(&上下文)?)
+        }
+    }
 
-        Ok((码表, 指标))
+    /// 贡献分析，与 Web::analyze 对应
+    pub fn analyze(&self, 目标函数配置: 目标配置) -> Result<贡献分析, 错误> {
+        let 输入 = self.参数.替换目标函数(目标函数配置);
+        let 上下文 = 默认上下文::新建(输入)?;
+        if 上下文.配置.encoder.chord.is_some() {
+            分析(&上下文, 并击编码器::新建(&上下文)?)
+        } else {
+            分析(&上下文, 默认编码器::新建(&上下文)?)
+        }
     }
 
     /// 优化，与 Web::optimize 对应  
     pub fn optimize(&self) -> Result<(), 错误> {
         let 优化方法配置 = self.参数.配置.clone().optimization.unwrap().metaheuristic.unwrap();
         let 上下文 = 默认上下文::新建(self.参数.clone())?;
-        let 编码器 = 默认编码器::新建(&上下文)?;
-        let mut 目标函数 = 默认目标函数::新建(&上下文, 编码器)?;
-        let mut 操作 = 默认操作::新建(&上下文)?;
         let 求解器配置::SimulatedAnnealing(退火) = 优化方法配置;
-        退火.优化(&上下文.初始决策, &mut 目标函数, &mut 操作, &上下文, self);
-        Ok(())
+        if 上下文.配置.encoder.chord.is_some() {
+            优化(&上下文, 并击编码器::新建(&上下文)?, &退火, self)
+        } else {
+            优化(&上下文, 默认编码器::新建(&上下文)?, &退火, self)
+        }
     }
 }
 
@@ -79,3 +86,133 @@ impl 界面 for WebApi {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::配置;
+    use crate::objectives::analysis::词贡献;
+    use crate::原始可编码对象;
+
+    // 键盘布局中 a 和 b 分属左右手，所以 ab 是一次换手；默认的 QWERTY 布局中它们都属于左手
+    const 测试配置: &str = r#"
+form:
+  alphabet: ab
+  mapping: {口: a, 日: b}
+encoder:
+  max_length: 2
+  select_keys: ["_"]
+  auto_select_length: 2
+optimization:
+  objective:
+    characters_full: {duplication: 1}
+  keyboard:
+    rows: ["ab"]
+    fingers: ["09"]
+  fingering_categories:
+    - name: 换手
+      predicate: {same_hand: false}
+"#;
+
+    #[test]
+    fn test_evaluate_keeps_fingering_settings() {
+        let 配置: 配置 = serde_yaml::from_str(测试配置).unwrap();
+        let mut 接口 = WebApi::new();
+        接口.sync(默认输入 {
+            配置,
+            词列表: vec![原始可编码对象 {
+                词: "一".to_string(),
+                元素序列: "口 日".to_string(),
+                频率: 1,
+                简码长度: u64::MAX,
+                固定排名: None,
+            }],
+            ..Default::default()
+        })
+        .unwrap();
+        let 目标函数配置: 目标配置 = serde_yaml::from_str(
+            "characters_full: {fingering: [null, null, null, null, null, null, 1, null]}",
+        )
+        .unwrap();
+        let (_, 指标) = 接口.encode_evaluate(目标函数配置).unwrap();
+        let 分组 = 指标.characters_full.unwrap();
+        assert_eq!(分组.fingering_labels, vec!["换手".to_string()]);
+        assert_eq!(分组.fingering.unwrap()[6], Some(1.0));
+    }
+
+    #[test]
+    fn test_analyze() {
+        let 配置: 配置 = serde_yaml::from_str(
+            r#"
+form:
+  alphabet: fjk
+  mapping: {口: f, 日: j, 木: k}
+encoder:
+  max_length: 3
+optimization:
+  objective:
+    characters_full: {duplication: 1}
+"#,
+        )
+        .unwrap();
+        let 词 = |词: &str, 元素序列: &str, 频率| 原始可编码对象 {
+            词: 词.to_string(),
+            元素序列: 元素序列.to_string(),
+            频率,
+            简码长度: u64::MAX,
+            固定排名: None,
+        };
+        let mut 接口 = WebApi::new();
+        接口.sync(默认输入 {
+            配置,
+            词列表: vec![
+                词("一", "口 日", 100),
+                词("二", "口 日", 30),
+                词("三", "木 口|口 日", 20),
+                词("四", "口 口 口", 5),
+            ],
+            原始当量信息: [("fj", 1.0), ("kf", 2.0), ("ff", 0.5)]
+                .iter()
+                .map(|(k, v)| (k.to_string(), *v))
+                .collect(),
+            ..Default::default()
+        })
+        .unwrap();
+        let 目标函数配置: 目标配置 =
+            serde_yaml::from_str("characters_full: {duplication: 1}").unwrap();
+        let 分析 = 接口.analyze(目标函数配置).unwrap();
+        // 三的容错码 fj 排在两个主编码之后，损失的频率包括二和三的容错码分得的一半频率
+        assert_eq!(分析.duplication.len(), 1);
+        assert_eq!(分析.duplication[0].code, "fj");
+        assert_eq!(分析.duplication[0].words, ["一", "二", "三"]);
+        assert_eq!(分析.duplication[0].lost_frequency, 40);
+        // 当量和差指法只统计主编码，贡献为词频与当量或差指法次数的乘积
+        let 列表 = |x: &[词贡献]| -> Vec<_> {
+            x.iter()
+                .map(|x| (x.word.clone(), x.code.clone(), x.value, x.contribution))
+                .collect()
+        };
+        let 期望 = |x: &[(&str, &str, f64, f64)]| -> Vec<_> {
+            x.iter()
+                .map(|(a, b, c, d)| (a.to_string(), b.to_string(), *c, *d))
+                .collect()
+        };
+        assert_eq!(
+            列表(&分析.equivalence),
+            期望(&[
+                ("一", "fj", 1.0, 100.0),
+                ("二", "fj", 1.0, 30.0),
+                ("三", "kf", 2.0, 20.0),
+                ("四", "fff", 1.0, 5.0),
+            ])
+        );
+        assert_eq!(列表(&分析.fingering), 期望(&[("四", "fff", 1.0, 5.0)]));
+        // 元素影响的词和频率只按主编码统计，同一个词中重复的元素只计一次
+        let 元素: Vec<_> = 分析
+            .elements
+            .iter()
+            .map(|x| (x.element.as_str(), x.words, x.frequency))
+            .collect();
+        assert_eq!(元素, [("口", 4, 145), ("日", 2, 130), ("木", 1, 10)]);
+    }
+}
@@ -1,10 +1,7 @@
-use crate::config::{目标配置, 优化配置, 求解器配置, 配置};
+use crate::config::{目标配置, 求解器配置, 配置};
 use crate::contexts::default::默认上下文;
-use crate::encoders::default::默认编码器;
-use crate::interfaces::{默认输入, 消息, 界面};
-use crate::objectives::default::默认目标函数;
-use crate::objectives::目标函数;
-use crate::operators::default::默认操作;
+use crate::encoders::{chord::并击编码器, default::默认编码器};
+use crate::interfaces::{分析, 优化, 编码评测, 默认输入, 消息, 界面};
 use console_error_panic_hook::set_once;
 use js_sys::Function;
 use serde::Serialize;
@@ -42,27 +39,37 @@ impl Web {
 
     pub fn encode_evaluate(&self, 前端目标函数配置: JsValue) -> Result<JsValue, JsError> {
         let 目标函数配置: 目标配置 = from_value(前端目标函数配置)?;
-        let mut 输入 = self.参数.clone();
-        输入.配置.optimization = Some(优化配置 {
-            objective: 目标函数配置,
-            metaheuristic: None,
-        });
+        let 输入 = self.参数.替换目标函数(目标函数配置);
         let 上下文 = 默认上下文::新建(输入)?;
-        let 编码器 = 默认编码器::新建(&上下文)?;
-        let mut 目标函数 = 默认目标函数::新建(&上下文, 编码器)?;
-        let (指标, _) = 目标函数.计算(&上下文.初始决策, &None);
-        let 码表 = 上下文.生成码表(&目标函数.编码结果);
-        Ok(to_value(&(码表, 指标))?)
+        let 结果 = if 上下文.配置.encoder.chord.is_some() {
+            编码评测(&上下文, 并击编码器::新建(&上下文)?)?
+        } else {
+            编码评测(&上下文, 默认编码器::新建(&上下文)?)?
+        };
+        Ok(to_value(&结果)?)
+    }
+
+    pub fn analyze(&self, 前端目标函数配置: JsValue) -> Result<JsValue, JsError> {
+        let 目标函数配置: 目标配置 = from_value(前端目标函数配置)?;
+        let 输入 = self.参数.替换目标函数(目标函数配置);
+        let 上下文 = 默认上下文::新建(输入)?;
+        let 结果 = if 上下文.配置.encoder.chord.is_some() {
+            分析(&上下文, 并击编码器::新建(&上下文)?)?
+        } else {
+            分析(&上下文, 默认编码器::新建(&上下文)?)?
+        };
+        Ok(to_value(&结果)?)
     }
 
     pub fn optimize(&self) -> Result<(), JsError> {
         let 优化方法配置 = self.参数.配置.clone().optimization.unwrap().metaheuristic.unwrap();
         let 上下文 = 默认上下文::新建(self.参数.clone())?;
-        let 编码器 = 默认编码器::新建(&上下文)?;
-        let mut 目标函数 = 默认目标函数::新建(&上下文, 编码器)?;
-        let mut 操作 = 默认操作::新建(&上下文)?;
         let 求解器配置::SimulatedAnnealing(退火) = 优化方法配置;
-        退火.优化(&上下文.初始决策, &mut 目标函数, &mut 操作, &上下文, self);
+        if 上下文.配置.encoder.chord.is_some() {
+            优化(&上下文, 并击编码器::新建(&上下文)?, &退火, self)?;
+        } else {
+            优化(&上下文, 默认编码器::新建(&上下文)?, &退火, self)?;
+        }
         Ok(())
     }
 }
@@ -13,9 +13,10 @@ pub mod optimizers;
 pub mod server;
 
 use config::{安排, 广义码位};
-use objectives::metric::指法标记;
+use contexts::拼写运算器;
+use objectives::metric::{指法标记, 自定义指法类别, 键盘几何};
 use rustc_hash::FxHashMap;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::cmp::Reverse;
 use std::io;
 use wasm_bindgen::JsError;
@@ -32,14 +33,25 @@ pub struct 原始可编码对象 {
     pub 词: String,
     pub 元素序列: String,
     pub 频率: u64,
-    #[serde(default = "原始可编码对象::默认级别")]
+    #[serde(
+        default = "原始可编码对象::默认级别",
+        deserialize_with = "原始可编码对象::读取级别"
+    )]
     pub 简码长度: u64,
+    #[serde(default)]
+    pub 固定排名: Option<u64>,
 }
 
 impl 原始可编码对象 {
     const fn 默认级别() -> u64 {
         u64::MAX
     }
+
+    // 只给出固定排名时，简码长度一列可以留空
+    fn 读取级别<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        let 级别: Option<u64> = Option::deserialize(deserializer)?;
+        Ok(级别.unwrap_or(Self::默认级别()))
+    }
 }
 
 pub type 原始键位分布信息 = FxHashMap<char, 键位分布损失函数>;
@@ -55,6 +67,88 @@ pub struct 键位分布损失函数 {
     pub 高于惩罚: f64,
 }
 
+/// 相邻两个按键之间的转移时间，为基础时间加上行距和列距分别乘以对应的系数
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct 转移时间 {
+    pub 基础: f64,
+    pub 每行: f64,
+    pub 每列: f64,
+}
+
+/// 打字时间模型，所有时间的单位均为毫秒
+///
+/// 一个编码的时间为各个按键的按键时间与相邻按键之间的转移时间之和。两个不同按键之间的转移时间由前后两个手指决定，没有单独给出的手指组合按照两个按键的关系分为同键、同指、同手和异手四种；不在键盘布局中的按键按异手计算，且不计行距和列距
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct 原始时间模型 {
+    pub 基础按键时间: f64,
+    pub 按键时间: FxHashMap<char, f64>,
+    pub 转移时间: [转移时间; 4],
+    /// 以前后两个手指的编号为下标，手指的编号与键盘布局中的相同
+    #[serde(default)]
+    pub 手指转移时间: [[Option<转移时间>; 10]; 10],
+    pub 选重开销: f64,
+    pub 自动上屏开销: f64,
+}
+
+impl 原始时间模型 {
+    /// 从制表符分隔的文本中读取时间模型，每行的第一列为参数类型：
+    ///
+    /// - `base 时间`：没有单独给出按键时间的按键的时间；
+    /// - `key 按键 时间`：单个按键的时间；
+    /// - `same_key`、`same_finger`、`same_hand`、`alternate` 后接基础时间、每行时间、每列时间：四种转移的时间；
+    /// - `finger 前指 后指` 后接基础时间、每行时间、每列时间：从前指到后指的转移时间，优先于上面四种；
+    /// - `select 时间`：选择首选以外的候选时额外花费的时间；
+    /// - `auto_commit 时间`：编码不以选择键结尾、自动上屏时额外花费的时间。
+    pub fn 解析(内容: &str) -> Result<Self, 错误> {
+        let mut 模型 = Self::default();
+        for (行号, 行) in 内容.lines().enumerate() {
+            let 列: Vec<_> = 行.split('\t').map(|x| x.trim()).collect();
+            if 列[0].is_empty() || 列[0].starts_with('#') {
+                continue;
+            }
+            let 错误 = || 错误::from(format!("时间模型第 {} 行格式错误：{行}", 行号 + 1));
+            let 数值 = |i: usize| -> Result<f64, 错误> {
+                列.get(i).and_then(|x| x.parse().ok()).ok_or_else(错误)
+            };
+            match 列[0] {
+                "base" => 模型.基础按键时间 = 数值(1)?,
+                "key" => {
+                    let 按键 = 列.get(1).and_then(|x| x.chars().next()).ok_or_else(错误)?;
+                    模型.按键时间.insert(按键, 数值(2)?);
+                }
+                "same_key" | "same_finger" | "same_hand" | "alternate" => {
+                    let 序号 = ["same_key", "same_finger", "same_hand", "alternate"]
+                        .iter()
+                        .position(|x| *x == 列[0])
+                        .unwrap();
+                    模型.转移时间[序号] = 转移时间 {
+                        基础: 数值(1)?,
+                        每行: 数值(2)?,
+                        每列: 数值(3)?,
+                    };
+                }
+                "finger" => {
+                    let 手指 = |i: usize| -> Result<usize, 错误> {
+                        列.get(i)
+                            .and_then(|x| x.parse().ok())
+                            .filter(|x| *x < 10)
+                            .ok_or_else(错误)
+                    };
+                    模型.手指转移时间[手指(1)?][手指(2)?] = Some(转移时间 {
+                        基础: 数值(3)?,
+                        每行: 数值(4)?,
+                        每列: 数值(5)?,
+                    });
+                }
+                "select" => 模型.选重开销 = 数值(1)?,
+                "auto_commit" => 模型.自动上屏开销 = 数值(1)?,
+                _ => return Err(错误()),
+            }
+        }
+        Ok(模型)
+    }
+}
+
 /// 元素用一个无符号整数表示
 pub type 元素 = usize;
 
@@ -79,6 +173,8 @@ pub struct 可编码对象 {
     pub 频率: u64,
     pub 简码长度: u64,
     pub 原始顺序: usize,
+    pub 容错: bool, // 容错码不出简码，和主编码共用原始顺序
+    pub 固定排名: Option<u64>,
 }
 
 /// 全码或简码的编码信息
@@ -90,20 +186,26 @@ pub struct 部分编码信息 {
     pub 选重标记: bool,       // 实际编码是否算作重码
     pub 上一个实际编码: 编码, // 前一个实际编码
     pub 上一个选重标记: bool, // 前一个实际编码是否算作重码
+    pub 上一个候选位置: u8,   // 前一个实际编码的选重位置
     pub 有变化: bool,         // 编码是否发生了变化
 }
 
 impl 部分编码信息 {
     #[inline(always)]
-    pub fn 更新(&mut self, 编码: 编码, 选重标记: bool) {
-        if self.实际编码 == 编码 && self.选重标记 == 选重标记 {
+    pub fn 更新(&mut self, 编码: 编码, 选重标记: bool, 候选位置: u8) {
+        if self.实际编码 == 编码
+            && self.选重标记 == 选重标记
+            && self.原始编码候选位置 == 候选位置
+        {
             return;
         }
         self.有变化 = true;
         self.上一个实际编码 = self.实际编码;
         self.上一个选重标记 = self.选重标记;
+        self.上一个候选位置 = self.原始编码候选位置;
         self.实际编码 = 编码;
         self.选重标记 = 选重标记;
+        self.原始编码候选位置 = 候选位置;
     }
 }
 
@@ -190,10 +292,67 @@ impl 棱镜 {
         chars
     }
 
+    /// 将空格分隔的元素序列解析为内部表示，元素名称在查找之前先经过拼写运算
+    pub fn 解析元素序列(
+        &self,
+        name: &str,
+        sequence: &str,
+        最大码长: usize,
+        拼写运算器: &拼写运算器,
+    ) -> Result<元素序列, 错误> {
+        let 原始元素序列: Vec<_> = sequence.split(' ').collect();
+        let mut 元素序列 = 元素序列::new();
+        let length = 原始元素序列.len();
+        if length > 最大码长 {
+            return Err(format!(
+                "编码对象「{name}」包含的元素数量为 {length}，超过了最大码长 {最大码长}"
+            )
+            .into());
+        }
+        for 原始元素 in 原始元素序列 {
+            let (元素名称, 位置) = if 原始元素.contains(".") {
+                let parts: Vec<&str> = 原始元素.split('.').collect();
+                if parts.len() != 2 {
+                    return Err(
+                        format!("编码对象「{name}」包含的元素「{原始元素}」格式不正确").into(),
+                    );
+                }
+                let index: usize = match parts[1].parse() {
+                    Ok(v) => v,
+                    Err(_) => {
+                        return Err(format!(
+                            "编码对象「{name}」包含的元素「{原始元素}」格式不正确"
+                        )
+                        .into());
+                    }
+                };
+                (parts[0], index)
+            } else {
+                (原始元素, 0)
+            };
+            let 改写名称 = 拼写运算器.改写元素名称(元素名称);
+            if let Some(元素) = self.元素转数字.get(&改写名称) {
+                元素序列.push((*元素, 位置));
+            } else if 改写名称 != 元素名称 {
+                return Err(format!(
+                    "编码对象「{name}」包含的元素「{原始元素}」经拼写运算改写为「{改写名称}」后无法在键盘映射中找到"
+                )
+                .into());
+            } else {
+                return Err(format!(
+                    "编码对象「{name}」包含的元素「{原始元素}」无法在键盘映射中找到"
+                )
+                .into());
+            }
+        }
+        Ok(元素序列)
+    }
+
     pub fn 预处理词列表(
         &self,
         原始词列表: Vec<原始可编码对象>,
         最大码长: usize,
+        拼写运算器: &拼写运算器,
     ) -> Result<Vec<可编码对象>, 错误> {
         let mut 词列表 = Vec::new();
         for (原始顺序, 原始可编码对象) in 原始词列表.into_iter().enumerate() {
@@ -202,68 +361,54 @@ impl 棱镜 {
                 频率: frequency,
                 元素序列: sequence,
                 简码长度: level,
+                固定排名: pinned,
             } = 原始可编码对象;
-            let 原始元素序列: Vec<_> = sequence.split(' ').collect();
-            let mut 元素序列 = 元素序列::new();
-            let length = 原始元素序列.len();
-            if length > 最大码长 {
-                return Err(format!(
-                    "编码对象「{name}」包含的元素数量为 {length}，超过了最大码长 {最大码长}"
-                )
-                .into());
-            }
-            for 原始元素 in 原始元素序列 {
-                let (元素, 位置) = if 原始元素.contains(".") {
-                    let parts: Vec<&str> = 原始元素.split('.').collect();
-                    if parts.len() != 2 {
-                        return Err(format!(
-                            "编码对象「{name}」包含的元素「{原始元素}」格式不正确"
-                        )
-                        .into());
+            // 多个元素序列之间用 | 分隔，每个序列可以用「:权重」的后缀指定它分得的频率比例
+            // 第一个序列为主编码，其余为容错码
+            let mut 序列列表 = vec![];
+            for 带权重序列 in sequence.split('|') {
+                let (序列, 权重) = match 带权重序列.rsplit_once(':') {
+                    Some((序列, 权重)) => {
+                        let 权重: f64 = 权重.parse().map_err(|_| {
+                            format!("编码对象「{name}」的元素序列「{带权重序列}」权重格式不正确")
+                        })?;
+                        (序列, 权重)
                     }
-                    let 元素名称 = parts[0];
-                    let index: usize = match parts[1].parse() {
-                        Ok(v) => v,
-                        Err(_) => {
-                            return Err(format!(
-                                "编码对象「{name}」包含的元素「{原始元素}」格式不正确"
-                            )
-                            .into());
-                        }
-                    };
-                    if let Some(元素) = self.元素转数字.get(元素名称) {
-                        元素序列.push((*元素, index));
-                    } else {
-                        return Err(format!(
-                            "编码对象「{name}」包含的元素「{原始元素}」无法在键盘映射中找到"
-                        )
-                        .into());
-                    }
-                    continue;
+                    None => (带权重序列, 1.0),
+                };
+                if !(权重.is_finite() && 权重 > 0.0) {
+                    return Err(format!(
+                        "编码对象「{name}」的元素序列「{带权重序列}」权重必须为正数"
+                    )
+                    .into());
+                }
+                let 元素序列 = self.解析元素序列(&name, 序列, 最大码长, 拼写运算器)?;
+                序列列表.push((元素序列, 权重));
+            }
+            let 总权重: f64 = 序列列表.iter().map(|(_, 权重)| 权重).sum();
+            let 序列数量 = 序列列表.len();
+            for (序号, (元素序列, 权重)) in 序列列表.into_iter().enumerate() {
+                let 频率 = if 序列数量 == 1 {
+                    frequency
                 } else {
-                    let 元素名称 = 原始元素;
-                    if let Some(元素) = self.元素转数字.get(元素名称) {
-                        (*元素, 0)
-                    } else {
-                        return Err(format!(
-                            "编码对象「{name}」包含的元素「{原始元素}」无法在键盘映射中找到"
-                        )
-                        .into());
-                    }
+                    (frequency as f64 * 权重 / 总权重).round() as u64
+                };
+                let 可编码对象 = 可编码对象 {
+                    词: name.clone(),
+                    词长: name.chars().count(),
+                    频率,
+                    简码长度: if 序号 == 0 { level } else { u64::MAX },
+                    元素序列,
+                    原始顺序,
+                    容错: 序号 > 0,
+                    固定排名: if 序号 == 0 { pinned } else { None },
                 };
-                元素序列.push((元素, 位置));
+                词列表.push((frequency, 可编码对象));
             }
-            词列表.push(可编码对象 {
-                词: name.clone(),
-                词长: name.chars().count(),
-                频率: frequency,
-                简码长度: level,
-                元素序列,
-                原始顺序,
-            });
         }
-        词列表.sort_by_key(|x| Reverse(x.频率));
-        Ok(词列表)
+        // 按照词的完整频率排序，而不是各个序列分得的频率；容错码排在所有主编码之后，不参与候选排序和层级
+        词列表.sort_by_key(|(频率, 词)| (词.容错, Reverse(*频率)));
+        Ok(词列表.into_iter().map(|(_, 词)| 词).collect())
     }
 
     /// 根据编码字符和未归一化的键位分布，生成一个理想的键位分布
@@ -318,45 +463,129 @@ impl 棱镜 {
         result
     }
 
+    /// 与速度当量相同，将编码空间内所有的编码组合预先计算好打字时间，不含选重和自动上屏的开销
+    pub fn 预处理时间信息(
+        &self,
+        时间模型: &原始时间模型,
+        空间: usize,
+        键盘几何: &键盘几何,
+    ) -> Vec<f64> {
+        let 按键时间 = |键: &char| *时间模型.按键时间.get(键).unwrap_or(&时间模型.基础按键时间);
+        let 转移 = |前: &char, 后: &char| {
+            let 位置 = (键盘几何.按键.get(前), 键盘几何.按键.get(后));
+            let (关系, 手指组合, 行距, 列距) = match 位置 {
+                _ if 前 == 后 => (0, None, 0, 0),
+                (Some(甲), Some(乙)) => {
+                    let 关系 = if 甲.左手 != 乙.左手 {
+                        3
+                    } else if 甲.手指 == 乙.手指 {
+                        1
+                    } else {
+                        2
+                    };
+                    let 手指组合 = Some((甲.手指编号(), 乙.手指编号()));
+                    (关系, 手指组合, 甲.行.abs_diff(乙.行), 甲.列.abs_diff(乙.列))
+                }
+                _ => (3, None, 0, 0),
+            };
+            let 时间 = 手指组合
+                .and_then(|(甲, 乙)| 时间模型.手指转移时间[甲][乙].as_ref())
+                .unwrap_or(&时间模型.转移时间[关系]);
+            时间.基础 + 时间.每行 * 行距 as f64 + 时间.每列 * 列距 as f64
+        };
+        (0..空间)
+            .map(|code| {
+                let chars = self.数字转编码(code as u64);
+                let 按键部分: f64 = chars.iter().map(按键时间).sum();
+                let 转移部分: f64 = chars.windows(2).map(|x| 转移(&x[0], &x[1])).sum();
+                按键部分 + 转移部分
+            })
+            .collect()
+    }
+
+    /// 与指法标记相同，将编码空间内所有的编码预先统计好左右手交替的组合数和落在基准行上的按键数，不在键盘布局中的按键不计入
+    pub fn 预处理换手与基准行(
+        &self, 空间: usize, 键盘几何: &键盘几何
+    ) -> Vec<[i64; 2]> {
+        (0..空间)
+            .map(|code| {
+                let chars = self.数字转编码(code as u64);
+                let 位置: Vec<_> = chars.iter().map(|x| 键盘几何.按键.get(x)).collect();
+                let 换手 = 位置
+                    .windows(2)
+                    .filter(|x| matches!(x, [Some(甲), Some(乙)] if 甲.左手 != 乙.左手))
+                    .count();
+                let 基准行 = 位置
+                    .iter()
+                    .filter(|x| x.is_some_and(|x| x.行 == 键盘几何.基准行))
+                    .count();
+                [换手 as i64, 基准行 as i64]
+            })
+            .collect()
+    }
+
+    /// 并击方案中，每个并击的当量按照其中按键的组合查表，与按键的书写顺序无关
+    /// 当量文件中按键集合相同的多个组合（如 ab 和 ba）都对应同一个并击，取它们的平均值
+    /// 表中的每个下标都只表示一个并击，所以表的大小是进制的并击长度次方；编码的当量由目标函数按并击切分后逐个查表相加
+    pub fn 预处理并击当量信息(
+        &self,
+        原始当量信息: &原始当量信息,
+        并击长度: usize,
+    ) -> Vec<f64> {
+        let mut 并击当量: FxHashMap<Vec<键>, (f64, usize)> = FxHashMap::default();
+        for (组合, 当量) in 原始当量信息 {
+            let 按键: Option<Vec<键>> = 组合
+                .chars()
+                .map(|c| self.键转数字.get(&c).copied())
+                .collect();
+            if let Some(mut 按键) = 按键 {
+                按键.sort_unstable();
+                按键.dedup();
+                let 累计 = 并击当量.entry(按键).or_default();
+                累计.0 += *当量;
+                累计.1 += 1;
+            }
+        }
+        let 空间 = self.进制.pow(并击长度 as u32) as usize;
+        (0..空间)
+            .map(|index| {
+                let mut 按键 = vec![];
+                let mut remainder = index as u64;
+                while remainder > 0 {
+                    let 键 = remainder % self.进制;
+                    if 键 != 0 {
+                        按键.push(键);
+                    }
+                    remainder /= self.进制;
+                }
+                按键.sort_unstable();
+                按键.dedup();
+                if 按键.len() < 2 {
+                    return 0.0;
+                }
+                match 并击当量.get(&按键) {
+                    Some((总当量, 数量)) => 总当量 / *数量 as f64,
+                    None => 0.0,
+                }
+            })
+            .collect()
+    }
+
     /// 将编码空间内所有的编码组合预先计算好差指法标记
     /// 标记压缩到一个 64 位整数中，每四位表示一个字符的差指法标记
-    /// 从低位到高位，依次是：同手、同指大跨排、同指小跨排、小指干扰、错手、三连击
+    /// 从低位到高位，依次是：同手、同指大跨排、同指小跨排、小指干扰、错手、三连击，以及至多两个自定义指法类别
     /// 按照这个字符串所对应的整数为下标，存储到一个大数组中
-    pub fn 预处理指法标记(&self, 空间: usize) -> Vec<指法向量> {
-        let 指法标记 = 指法标记::new();
+    pub fn 预处理指法标记(
+        &self,
+        空间: usize,
+        键盘几何: &键盘几何,
+        自定义指法: &[自定义指法类别],
+    ) -> Vec<指法向量> {
+        let 指法标记 = 指法标记::new(键盘几何);
         let mut result: Vec<指法向量> = Vec::with_capacity(空间);
         for code in 0..空间 {
             let chars = self.数字转编码(code as u64);
-            if chars.len() < 2 {
-                result.push(指法向量::default());
-                continue;
-            }
-            let mut 指法向量 = 指法向量::default();
-            for i in 0..(chars.len() - 1) {
-                let pair = (chars[i], chars[i + 1]);
-                if 指法标记.同手.contains(&pair) {
-                    指法向量[0] += 1;
-                }
-                if 指法标记.同指大跨排.contains(&pair) {
-                    指法向量[1] += 1;
-                }
-                if 指法标记.同指小跨排.contains(&pair) {
-                    指法向量[2] += 1;
-                }
-                if 指法标记.小指干扰.contains(&pair) {
-                    指法向量[3] += 1;
-                }
-                if 指法标记.错手.contains(&pair) {
-                    指法向量[4] += 1;
-                }
-            }
-            for i in 0..(chars.len() - 2) {
-                let triple = (chars[i], chars[i + 1], chars[i + 2]);
-                if triple.0 == triple.1 && triple.1 == triple.2 {
-                    指法向量[5] += 1;
-                }
-            }
-            result.push(指法向量);
+            result.push(指法标记.计算指法向量(&chars, 自定义指法));
         }
         result
     }
@@ -1,14 +1,72 @@
 use chai::config::求解器配置;
-use chai::contexts::default::默认上下文;
+use chai::contexts::default::{默认上下文, 默认决策};
+use chai::encoders::chord::并击编码器;
 use chai::encoders::default::默认编码器;
+use chai::encoders::编码器;
 use chai::interfaces::command_line::{
     从命令行参数创建, 命令, 命令行, 默认命令行参数
 };
+use chai::objectives::analysis::{贡献分析, 默认条目数};
+use chai::objectives::corpus::{分词方法, 语料评测器};
 use chai::objectives::{default::默认目标函数, 目标函数};
 use chai::operators::default::默认操作;
-use chai::错误;
+use chai::optimizers::simulated_annealing::退火方法;
+use chai::{原始当量信息, 错误};
 use clap::Parser;
-use std::thread::spawn;
+use std::fs::read_to_string;
+use std::thread::{spawn, JoinHandle};
+
+fn 编码并评测<E: 编码器<决策 = 默认决策>>(
+    命令行: &命令行<默认命令行参数>,
+    上下文: &默认上下文,
+    编码器: E,
+) -> Result<(), 错误> {
+    let mut 目标函数 = 默认目标函数::新建(上下文, 编码器)?;
+    let (mut 指标, _) = 目标函数.计算(&上下文.初始决策, &None);
+    目标函数.附加说明(&mut 指标);
+    let 码表 = 上下文.生成码表(&目标函数.编码结果);
+    命令行.输出编码结果(码表);
+    命令行.输出评测指标(指标);
+    命令行.输出贡献分析(贡献分析::新建(上下文, &目标函数, &上下文.初始决策, 默认条目数));
+    Ok(())
+}
+
+fn 编码并评测语料<E: 编码器<决策 = 默认决策>>(
+    命令行: &命令行<默认命令行参数>,
+    上下文: &默认上下文,
+    编码器: E,
+    原始当量信息: &原始当量信息,
+    语料: &str,
+    分词方法: 分词方法,
+) -> Result<(), 错误> {
+    let mut 目标函数 = 默认目标函数::新建(上下文, 编码器)?;
+    目标函数.计算(&上下文.初始决策, &None);
+    let 评测器 = 语料评测器::新建(上下文, &目标函数.编码结果, 原始当量信息);
+    命令行.输出语料指标(评测器.评测(语料, 分词方法));
+    Ok(())
+}
+
+fn 启动优化线程<E: 编码器<决策 = 默认决策> + Send + 'static>(
+    上下文: &默认上下文,
+    编码器: E,
+    优化方法: 退火方法,
+    子命令行: 命令行<默认命令行参数>,
+) -> Result<JoinHandle<(bool, f64, String)>, 错误> {
+    let mut 目标函数 = 默认目标函数::新建(上下文, 编码器)?;
+    let mut 操作 = 默认操作::新建(上下文)?;
+    let _上下文 = 上下文.clone();
+    Ok(spawn(move || {
+        let 优化结果 = 优化方法.优化(
+            &_上下文.初始决策,
+            &mut 目标函数,
+            &mut 操作,
+            &_上下文,
+            &子命令行,
+        );
+        let 可行 = 默认目标函数::<E>::可行(&优化结果.指标);
+        (可行, 优化结果.分数, format!("{}", 优化结果.指标))
+    }))
+}
 
 fn main() -> Result<(), 错误> {
     let 参数 = 默认命令行参数::parse();
@@ -28,14 +86,39 @@ fn main() -> Result<(), 错误> {
                 command: 命令::Encode { data: data.clone() },
             };
             let 命令行 = 命令行::新建(重构参数, None);
-            let 输入 = 从命令行参数创建(&命令行.参数);
+            let 输入 = 从命令行参数创建(&命令行.参数)?;
+            let 上下文 = 默认上下文::新建(输入)?;
+            if 上下文.配置.encoder.chord.is_some() {
+                编码并评测(&命令行, &上下文, 并击编码器::新建(&上下文)?)?;
+            } else {
+                编码并评测(&命令行, &上下文, 默认编码器::新建(&上下文)?)?;
+            }
+        }
+        命令::EvaluateCorpus {
+            data,
+            corpus,
+            segmenter,
+        } => {
+            let 语料 = read_to_string(&corpus)
+                .map_err(|e| format!("无法读取语料文件 {}：{e}", corpus.display()))?;
+            let 重构参数 = 默认命令行参数 {
+                command: 命令::EvaluateCorpus {
+                    data: data.clone(),
+                    corpus,
+                    segmenter,
+                },
+            };
+            let 命令行 = 命令行::新建(重构参数, None);
+            let 输入 = 从命令行参数创建(&命令行.参数)?;
+            let 原始当量信息 = 输入.原始当量信息.clone();
             let 上下文 = 默认上下文::新建(输入)?;
-            let 编码器 = 默认编码器::新建(&上下文)?;
-            let mut 目标函数 = 默认目标函数::新建(&上下文, 编码器)?;
-            let (指标, _) = 目标函数.计算(&上下文.初始决策, &None);
-            let 码表 = 上下文.生成码表(&目标函数.编码结果);
-            命令行.输出编码结果(码表);
-            命令行.输出评测指标(指标);
+            if 上下文.配置.encoder.chord.is_some() {
+                let 编码器 = 并击编码器::新建(&上下文)?;
+                编码并评测语料(&命令行, &上下文, 编码器, &原始当量信息, &语料, segmenter)?;
+            } else {
+                let 编码器 = 默认编码器::新建(&上下文)?;
+                编码并评测语料(&命令行, &上下文, 编码器, &原始当量信息, &语料, segmenter)?;
+            }
         }
         命令::Optimize { data, threads } => {
             let 重构参数 = 默认命令行参数 {
@@ -45,7 +128,7 @@ fn main() -> Result<(), 错误> {
                 },
             };
             let 命令行 = 命令行::新建(重构参数, None);
-            let 输入 = 从命令行参数创建(&命令行.参数);
+            let 输入 = 从命令行参数创建(&命令行.参数)?;
             let _config = 输入.配置.clone();
             let 退火 = match _config.optimization {
                 Some(opt) => match opt.metaheuristic {
@@ -57,30 +140,27 @@ fn main() -> Result<(), 错误> {
             let 上下文 = 默认上下文::新建(输入)?;
             let mut 线程池 = vec![];
             for 线程序号 in 0..threads {
-                let 编码器 = 默认编码器::新建(&上下文)?;
-                let mut 目标函数 = 默认目标函数::新建(&上下文, 编码器)?;
-                let mut 操作 = 默认操作::新建(&上下文)?;
                 let 优化方法 = 退火.clone();
                 let 子命令行 = 命令行.生成子命令行(线程序号);
-                let _上下文 = 上下文.clone();
-                let 线程 = spawn(move || {
-                    优化方法.优化(
-                        &_上下文.初始决策,
-                        &mut 目标函数,
-                        &mut 操作,
-                        &_上下文,
-                        &子命令行,
-                    )
-                });
+                let 线程 = if 上下文.配置.encoder.chord.is_some() {
+                    let 编码器 = 并击编码器::新建(&上下文)?;
+                    启动优化线程(&上下文, 编码器, 优化方法, 子命令行)?
+                } else {
+                    let 编码器 = 默认编码器::新建(&上下文)?;
+                    启动优化线程(&上下文, 编码器, 优化方法, 子命令行)?
+                };
                 线程池.push((线程序号, 线程));
             }
             let mut 优化结果列表 = vec![];
             for (线程序号, 线程) in 线程池 {
                 优化结果列表.push((线程序号, 线程.join().unwrap()));
             }
-            优化结果列表.sort_by(|a, b| a.1.分数.partial_cmp(&b.1.分数).unwrap());
-            for (线程序号, 优化结果) in 优化结果列表 {
-                print!("线程{}：{}", 线程序号, 优化结果.指标);
+            // 可行的结果排在不可行的结果之前，可行性相同时按分数从低到高排列
+            优化结果列表.sort_by(|(_, (甲可行, 甲, _)), (_, (乙可行, 乙, _))| {
+                乙可行.cmp(甲可行).then(甲.total_cmp(乙))
+            });
+            for (线程序号, (_, _, 指标)) in 优化结果列表 {
+                print!("线程{}：{}", 线程序号, 指标);
             }
         }
     }
@@ -0,0 +1,315 @@
+//! 贡献分析
+//!
+//! 默认指标只给出各项的总和，这里把总和拆回到每个词和每个元素上，列出对各项指标贡献最大的词，便于找出方案的问题所在。
+
+use super::default::默认目标函数;
+use super::metric::内置指法数量;
+use crate::contexts::default::{默认上下文, 默认决策};
+use crate::encoders::编码器;
+use crate::{编码, 编码信息};
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+/// 每一项列表中最多列出的条目数
+pub const 默认条目数: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct 重码组 {
+    pub code: String,
+    pub words: Vec<String>,
+    pub lost_frequency: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct 词贡献 {
+    pub word: String,
+    pub code: String,
+    pub frequency: u64,
+    pub value: f64,
+    pub contribution: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct 元素贡献 {
+    pub element: String,
+    pub words: usize,
+    pub frequency: u64,
+}
+
+/// 各项指标中贡献最大的词，以及每个元素影响的总频率
+///
+/// 元素影响的词按照决策中选择的拆分统计，所以优化了拆分之后分析的是当前的拆分，而不是词列表中的初始拆分
+///
+/// 重码组按全码分组，容错码也和编码器中一样占用候选位置，损失的频率为所有非首选词的频率之和；当量和差指法按全码的实际编码计算，贡献为词频与该编码的当量或差指法次数（只含内置的差指法，不含同手）的乘积。自定义指法类别可能是好的指法，所以不计入差指法
+///
+/// 误触按词实际使用的编码（有简码时为简码）计算：把原始编码中的一个按键换成键盘上与它相邻的按键，末尾的选择键保持不变，如果得到的恰好是另一个词的实际编码，就会在不知不觉中打出错误的词；多个词的实际编码相同时，打出的是候选位置靠前的那个。每个词的值为这样的误触占所有单键相邻误触的比例，mistype_rate 为它按词频加权的平均值。误触只在分析中报告，不计入目标函数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct 贡献分析 {
+    pub duplication: Vec<重码组>,
+    pub equivalence: Vec<词贡献>,
+    pub fingering: Vec<词贡献>,
+    pub mistype_rate: f64,
+    pub mistype: Vec<词贡献>,
+    pub elements: Vec<元素贡献>,
+}
+
+impl 贡献分析 {
+    pub fn 新建<E: 编码器>(
+        上下文: &默认上下文,
+        目标函数: &默认目标函数<E>,
+        决策: &默认决策,
+        条目数: usize,
+    ) -> Self {
+        let 编码结果 = &目标函数.编码结果;
+        let 参数 = &目标函数.参数;
+        let 转编码 = |code: 编码| 上下文.棱镜.数字转编码(code).iter().collect::<String>();
+        let mut 重码组列表: FxHashMap<编码, Vec<(u8, usize)>> = FxHashMap::default();
+        let mut 当量列表 = vec![];
+        let mut 指法列表 = vec![];
+        let mut 元素统计: FxHashMap<usize, (usize, u64)> = FxHashMap::default();
+        let 元素序列列表 = 上下文.当前元素序列(决策);
+        for (序号, 词) in 上下文.词列表.iter().enumerate() {
+            let 全码 = &编码结果[序号].全码;
+            // 容错码和主编码一样占用候选位置，所以也参与重码分组
+            重码组列表
+                .entry(全码.原始编码)
+                .or_default()
+                .push((全码.原始编码候选位置, 序号));
+            if 词.容错 {
+                continue;
+            }
+            let 当量 = 参数.编码当量(全码.实际编码);
+            let 差指法: u64 = 参数.编码指法(全码.实际编码)[1..内置指法数量]
+                .iter()
+                .map(|x| *x as u64)
+                .sum();
+            let 构造 = |value: f64| 词贡献 {
+                word: 词.词.clone(),
+                code: 转编码(全码.实际编码),
+                frequency: 词.频率,
+                value,
+                contribution: value * 词.频率 as f64,
+            };
+            当量列表.push(构造(当量));
+            if 差指法 > 0 {
+                指法列表.push(构造(差指法 as f64));
+            }
+            let mut 已统计 = vec![];
+            for (元素, _) in 元素序列列表[序号] {
+                if 已统计.contains(元素) {
+                    continue;
+                }
+                已统计.push(*元素);
+                let 统计 = 元素统计.entry(*元素).or_default();
+                统计.0 += 1;
+                统计.1 += 词.频率;
+            }
+        }
+        let mut duplication: Vec<_> = 重码组列表
+            .into_iter()
+            .filter(|(_, 组)| 组.len() > 1)
+            .map(|(code, mut 组)| {
+                组.sort();
+                重码组 {
+                    code: 转编码(code),
+                    words: 组
+                        .iter()
+                        .map(|(_, i)| 上下文.词列表[*i].词.clone())
+                        .collect(),
+                    lost_frequency: 组[1..].iter().map(|(_, i)| 上下文.词列表[*i].频率).sum(),
+                }
+            })
+            .collect();
+        duplication.sort_by_key(|x| Reverse(x.lost_frequency));
+        duplication.truncate(条目数);
+        for 列表 in [&mut 当量列表, &mut 指法列表] {
+            列表.sort_by(|a, b| b.contribution.total_cmp(&a.contribution));
+            列表.truncate(条目数);
+        }
+        let mut elements: Vec<_> = 元素统计
+            .into_iter()
+            .map(|(元素, (words, frequency))| 元素贡献 {
+                element: 上下文.棱镜.数字转元素[&元素].clone(),
+                words,
+                frequency,
+            })
+            .collect();
+        elements.sort_by_key(|x| Reverse(x.frequency));
+        let (mistype_rate, mut 误触列表) = Self::误触分析(上下文, 编码结果);
+        误触列表.sort_by(|a, b| b.contribution.total_cmp(&a.contribution));
+        误触列表.truncate(条目数);
+        Self {
+            duplication,
+            equivalence: 当量列表,
+            fingering: 指法列表,
+            mistype_rate,
+            mistype: 误触列表,
+            elements,
+        }
+    }
+
+    fn 误触分析(
+        上下文: &默认上下文, 编码结果: &[编码信息]
+    ) -> (f64, Vec<词贡献>) {
+        let 棱镜 = &上下文.棱镜;
+        let 键盘几何 = &上下文.键盘几何;
+        let 进制 = 棱镜.进制;
+        let 相邻按键: Vec<Vec<u64>> = (0..进制)
+            .map(|键| {
+                let Some(键) = 棱镜.数字转键.get(&键) else {
+                    return vec![];
+                };
+                (1..进制)
+                    .filter(|x| 键盘几何.相邻(*键, 棱镜.数字转键[x]))
+                    .collect()
+            })
+            .collect();
+        let 编码到词 = Self::上屏词表(上下文, 编码结果);
+        let mut 误触列表 = vec![];
+        let mut 总频率 = 0;
+        let mut 总贡献 = 0.0;
+        for (序号, 词) in 上下文.词列表.iter().enumerate() {
+            if 词.容错 {
+                continue;
+            }
+            let 结果 = &编码结果[序号];
+            let 部分编码信息 = if 结果.简码.实际编码 != 0 {
+                &结果.简码
+            } else {
+                &结果.全码
+            };
+            let 编码 = 部分编码信息.实际编码;
+            let mut 误触数 = 0;
+            let mut 错词数 = 0;
+            let mut 乘数 = 1;
+            // 只替换原始编码中的按键，选择键按错属于选重的问题
+            while 乘数 <= 部分编码信息.原始编码 {
+                let 键 = 编码 / 乘数 % 进制;
+                for 相邻键 in &相邻按键[键 as usize] {
+                    误触数 += 1;
+                    let 误触编码 = 编码 - 键 * 乘数 + 相邻键 * 乘数;
+                    if let Some(另一个词) = 编码到词.get(&误触编码) {
+                        if 上下文.词列表[*另一个词].词 != 词.词 {
+                            错词数 += 1;
+                        }
+                    }
+                }
+                乘数 *= 进制;
+            }
+            总频率 += 词.频率;
+            if 错词数 == 0 {
+                continue;
+            }
+            let value = 错词数 as f64 / 误触数 as f64;
+            总贡献 += value * 词.频率 as f64;
+            误触列表.push(词贡献 {
+                word: 词.词.clone(),
+                code: 棱镜.数字转编码(编码).iter().collect(),
+                frequency: 词.频率,
+                value,
+                contribution: value * 词.频率 as f64,
+            });
+        }
+        (总贡献 / 总频率.max(1) as f64, 误触列表)
+    }
+
+    /// 每个实际编码打出的词：实际编码相同的词中，取候选位置最靠前的，候选位置也相同时取候选次序最靠前的
+    fn 上屏词表(
+        上下文: &默认上下文, 编码结果: &[编码信息]
+    ) -> FxHashMap<编码, usize> {
+        let 候选次序 = 上下文.计算候选次序();
+        let mut 编码到词: FxHashMap<编码, (u8, usize, usize)> = FxHashMap::default();
+        for (序号, 结果) in 编码结果.iter().enumerate() {
+            for 部分编码信息 in [&结果.全码, &结果.简码] {
+                if 部分编码信息.实际编码 == 0 {
+                    continue;
+                }
+                let 项 = (部分编码信息.原始编码候选位置, 候选次序[序号], 序号);
+                编码到词
+                    .entry(部分编码信息.实际编码)
+                    .and_modify(|x| *x = (*x).min(项))
+                    .or_insert(项);
+            }
+        }
+        编码到词.into_iter().map(|(k, v)| (k, v.2)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoders::default::默认编码器;
+    use crate::interfaces::默认输入;
+    use crate::objectives::目标函数;
+    use crate::原始可编码对象;
+
+    const 测试配置: &str = r#"
+form:
+  alphabet: qwera
+  mapping: {口: q, 日: w, 木: e, 水: a}
+encoder:
+  max_length: 3
+  select_keys: [d]
+  auto_select_length: 3
+  candidate_order: original
+optimization:
+  objective:
+    characters_full: {duplication: 1}
+"#;
+
+    #[test]
+    fn test_mistype() {
+        let 词列表 = [
+            ("三", "口 日", 5),
+            ("一", "口 日", 100),
+            ("二", "口 日", 50),
+            ("四", "口 木", 40),
+        ]
+        .iter()
+        .map(|(词, 元素序列, 频率)| 原始可编码对象 {
+            词: 词.to_string(),
+            元素序列: 元素序列.to_string(),
+            频率: *频率,
+            简码长度: u64::MAX,
+            固定排名: None,
+        })
+        .collect();
+        let 上下文 = 默认上下文::新建(默认输入 {
+            配置: serde_yaml::from_str(测试配置).unwrap(),
+            词列表,
+            ..Default::default()
+        })
+        .unwrap();
+        let 编码器 = 默认编码器::新建(&上下文).unwrap();
+        let mut 目标函数 = 默认目标函数::新建(&上下文, 编码器).unwrap();
+        目标函数.计算(&上下文.初始决策, &None);
+        // 三、一、二都是 qwd，按原始顺序三是首选，所以打 qwd 得到的是三
+        let 编码到词 = 贡献分析::上屏词表(&上下文, &目标函数.编码结果);
+        let 棱镜 = &上下文.棱镜;
+        let 编码 = |x: &str| {
+            x.chars()
+                .rev()
+                .fold(0, |y, c| y * 棱镜.进制 + 棱镜.键转数字[&c])
+        };
+        assert_eq!(上下文.词列表[编码到词[&编码("qwd")]].词, "三");
+        // qwd 中 w 误触为相邻的 e 得到四，qed 中 e 误触为 w 得到三，各占 5 种误触中的 1 种；
+        // 末尾的选择键 d 不参与误触，否则 d 与 e、r 相邻，会多出 2 种误触
+        let 分析 = 贡献分析::新建(&上下文, &目标函数, &上下文.初始决策, 默认条目数);
+        let 误触: Vec<_> = 分析
+            .mistype
+            .iter()
+            .map(|x| (x.word.as_str(), x.code.as_str(), x.value))
+            .collect();
+        assert_eq!(
+            误触,
+            [
+                ("一", "qwd", 0.2),
+                ("二", "qwd", 0.2),
+                ("四", "qed", 0.2),
+                ("三", "qwd", 0.2)
+            ]
+        );
+        assert!((分析.mistype_rate - 0.2).abs() < 1e-12);
+    }
+}
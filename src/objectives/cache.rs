@@ -7,9 +7,10 @@ use super::metric::LevelMetricUniform;
 use super::metric::分组指标;
 use super::metric::层级指标;
 use super::metric::键长指标;
-use crate::config::部分权重;
+use crate::config::{分布损失, 部分权重};
+use crate::contexts::default::层级范围;
 use crate::{
-    最大按键组合长度, 编码, 部分编码信息, 键位分布损失函数
+    编码, 部分编码信息, 键位分布损失函数
 };
 use std::iter::zip;
 
@@ -17,21 +18,30 @@ use std::iter::zip;
 #[derive(Debug, Clone)]
 pub struct 缓存 {
     partial_weights: 部分权重,
-    total_count: usize,
+    tiers_range: Vec<层级范围>,
     total_frequency: i64,
     total_pairs: i64,
-    total_extended_pairs: i64,
     distribution: Vec<i64>,
     total_pair_equivalence: f64,
-    total_extended_pair_equivalence: f64,
+    first_key_frequency: Vec<i64>,
+    last_key_frequency: Vec<i64>,
     total_duplication: i64,
+    total_selection: f64,
+    total_time: f64,
+    total_characters: i64,
     total_fingering: [i64; 8],
+    total_alternation: i64,
+    total_home_row: i64,
     total_levels: Vec<i64>,
     tiers_duplication: Vec<i64>,
     tiers_levels: Vec<Vec<i64>>,
     tiers_fingering: Vec<[i64; 8]>,
-    max_index: u64,
-    segment: u64,
+    tiers_pairs: Vec<i64>,
+    tiers_pair_equivalence: Vec<f64>,
+    tiers_distribution: Vec<Vec<i64>>,
+    tiers_alternation: Vec<i64>,
+    tiers_home_row: Vec<i64>,
+    tiers_keys: Vec<i64>,
     length_breakpoints: Vec<u64>,
     radix: u64,
 }
@@ -49,18 +59,21 @@ impl 缓存 {
             return;
         }
         编码信息.有变化 = false;
-        self.增减(序号, 频率, 编码信息.实际编码, 编码信息.选重标记, 参数, 1);
+        let 当前 = (
+            编码信息.实际编码,
+            编码信息.选重标记,
+            编码信息.原始编码候选位置,
+        );
+        self.增减(序号, 频率, 当前, 参数, 1);
         if 编码信息.上一个实际编码 == 0 {
             return;
         }
-        self.增减(
-            序号,
-            频率,
+        let 上一个 = (
             编码信息.上一个实际编码,
             编码信息.上一个选重标记,
-            参数,
-            -1,
+            编码信息.上一个候选位置,
         );
+        self.增减(序号, 频率, 上一个, 参数, -1);
     }
 
     pub fn 汇总(&self, 参数: &默认目标函数参数) -> (分组指标, f64) {
@@ -71,24 +84,31 @@ impl 缓存 {
             tiers: None,
             key_distribution: None,
             key_distribution_loss: None,
+            finger_distribution: None,
+            finger_distribution_loss: None,
+            hand_distribution: None,
+            hand_distribution_loss: None,
             pair_equivalence: None,
             extended_pair_equivalence: None,
             fingering: None,
+            fingering_labels: vec![],
             duplication: None,
             levels: None,
+            selection: None,
+            typing_time: None,
+            alternation: None,
+            home_row: None,
         };
         let mut 损失函数 = 0.0;
         // 一、全局指标
         // 1. 按键分布
+        let 损失类型 = partial_weights.key_distribution_loss.unwrap_or_default();
+        let 总频率: i64 = self.distribution.iter().sum();
+        let 总频率 = 总频率.max(1) as f64;
         if let Some(key_distribution_weight) = partial_weights.key_distribution {
             // 首先归一化
-            let 总频率: i64 = self.distribution.iter().sum();
-            let 分布 = self
-                .distribution
-                .iter()
-                .map(|x| *x as f64 / 总频率 as f64)
-                .collect();
-            let 距离 = 缓存::计算键位分布距离(&分布, 键位分布信息);
+            let 分布: Vec<_> = self.distribution.iter().map(|x| *x as f64 / 总频率).collect();
+            let 距离 = 缓存::计算键位分布距离(&分布, 键位分布信息, 损失类型);
             let mut 分布映射 = FxHashMap::default();
             for (i, x) in 分布.iter().enumerate() {
                 if let Some(键) = 参数.数字转键.get(&(i as u64)) {
@@ -99,6 +119,36 @@ impl 缓存 {
             分组指标.key_distribution_loss = Some(距离);
             损失函数 += 距离 * key_distribution_weight;
         }
+        // 按手指和左右手汇总的分布，只统计键盘布局中有的按键
+        let 汇总分布 = |按键分布: &[i64], 组数: usize, 分组: &dyn Fn(usize) -> Option<usize>| {
+            let mut 分布 = vec![0.0; 组数];
+            for (键, x) in 按键分布.iter().enumerate() {
+                if let Some(组) = 分组(键) {
+                    分布[组] += *x as f64;
+                }
+            }
+            let 总和: f64 = 分布.iter().sum();
+            if 总和 > 0.0 {
+                分布.iter_mut().for_each(|x| *x /= 总和);
+            }
+            分布
+        };
+        if let Some(finger_distribution_weight) = partial_weights.finger_distribution {
+            let 分布 = 汇总分布(&self.distribution, 10, &|键| 参数.按键手指[键]);
+            let 距离 = 缓存::计算键位分布距离(&分布, &参数.手指分布信息, 损失类型);
+            分组指标.finger_distribution = Some(分布);
+            分组指标.finger_distribution_loss = Some(距离);
+            损失函数 += 距离 * finger_distribution_weight;
+        }
+        if let Some(hand_distribution_weight) = partial_weights.hand_distribution {
+            let 分布 = 汇总分布(&self.distribution, 2, &|键| {
+                参数.按键手指[键].map(|x| x / 5)
+            });
+            let 距离 = 缓存::计算键位分布距离(&分布, &参数.左右手分布信息, 损失类型);
+            分组指标.hand_distribution = Some(分布);
+            分组指标.hand_distribution_loss = Some(距离);
+            损失函数 += 距离 * hand_distribution_weight;
+        }
         // 2. 组合当量
         if let Some(equivalence_weight) = partial_weights.pair_equivalence {
             let equivalence = self.total_pair_equivalence / self.total_pairs as f64;
@@ -107,8 +157,7 @@ impl 缓存 {
         }
         // 3. 词间当量
         if let Some(equivalence_weight) = partial_weights.extended_pair_equivalence {
-            let equivalence =
-                self.total_extended_pair_equivalence / self.total_extended_pairs as f64;
+            let equivalence = self.计算词间当量(参数);
             分组指标.extended_pair_equivalence = Some(equivalence);
             损失函数 += equivalence * equivalence_weight;
         }
@@ -142,19 +191,53 @@ impl 缓存 {
             }
             分组指标.levels = Some(levels);
         }
+        // 7. 选择代价
+        if let Some(selection_weight) = partial_weights.selection {
+            let selection = self.total_selection / self.total_frequency as f64;
+            分组指标.selection = Some(selection);
+            损失函数 += selection * selection_weight;
+        }
+        // 8. 打字时间
+        if let Some(time_weight) = partial_weights.typing_time {
+            let typing_time = self.total_time / self.total_characters.max(1) as f64;
+            分组指标.typing_time = Some(typing_time);
+            损失函数 += typing_time * time_weight;
+        }
+        // 9. 换手率和基准行使用率
+        if let Some(alternation_weight) = partial_weights.alternation {
+            let alternation = self.total_alternation as f64 / self.total_pairs.max(1) as f64;
+            分组指标.alternation = Some(alternation);
+            损失函数 += alternation * alternation_weight;
+        }
+        if let Some(home_row_weight) = partial_weights.home_row {
+            let total_keys = self.total_pairs + self.total_frequency;
+            let home_row = self.total_home_row as f64 / total_keys.max(1) as f64;
+            分组指标.home_row = Some(home_row);
+            损失函数 += home_row * home_row_weight;
+        }
         // 二、分级指标
         if let Some(tiers_weight) = &partial_weights.tiers {
             let mut tiers: Vec<层级指标> = tiers_weight
                 .iter()
                 .map(|x| 层级指标 {
                     top: x.top,
+                    coverage: x.coverage,
+                    charset: x.charset.clone(),
                     duplication: None,
                     levels: None,
                     fingering: None,
+                    fingering_labels: vec![],
+                    pair_equivalence: None,
+                    key_distribution: None,
+                    key_distribution_loss: None,
+                    finger_distribution: None,
+                    finger_distribution_loss: None,
+                    alternation: None,
+                    home_row: None,
                 })
                 .collect();
             for (itier, tier_weights) in tiers_weight.iter().enumerate() {
-                let count = tier_weights.top.unwrap_or(self.total_count) as f64;
+                let count = self.tiers_range[itier].大小().max(1) as f64;
                 // 1. 重码
                 if let Some(duplication_weight) = tier_weights.duplication {
                     let duplication = self.tiers_duplication[itier];
@@ -190,6 +273,53 @@ impl 缓存 {
                     }
                     tiers[itier].fingering = Some(fingering);
                 }
+                // 4. 组合当量
+                if let Some(equivalence_weight) = tier_weights.pair_equivalence {
+                    let pairs = self.tiers_pairs[itier].max(1) as f64;
+                    let equivalence = self.tiers_pair_equivalence[itier] / pairs;
+                    损失函数 += equivalence * equivalence_weight;
+                    tiers[itier].pair_equivalence = Some(equivalence);
+                }
+                // 5. 按键分布
+                if let Some(key_distribution_weight) = tier_weights.key_distribution {
+                    let 分布 = &self.tiers_distribution[itier];
+                    let 总数: i64 = 分布.iter().sum();
+                    let 分布: Vec<_> = 分布.iter().map(|x| *x as f64 / 总数.max(1) as f64).collect();
+                    let 距离 = 缓存::计算键位分布距离(&分布, 键位分布信息, 损失类型);
+                    let mut 分布映射 = FxHashMap::default();
+                    for (i, x) in 分布.iter().enumerate() {
+                        if let Some(键) = 参数.数字转键.get(&(i as u64)) {
+                            分布映射.insert(*键, *x);
+                        }
+                    }
+                    损失函数 += 距离 * key_distribution_weight;
+                    tiers[itier].key_distribution = Some(分布映射);
+                    tiers[itier].key_distribution_loss = Some(距离);
+                }
+                // 6. 手指分布
+                if let Some(finger_distribution_weight) = tier_weights.finger_distribution {
+                    let 分布 = 汇总分布(&self.tiers_distribution[itier], 10, &|键| {
+                        参数.按键手指[键]
+                    });
+                    let 距离 =
+                        缓存::计算键位分布距离(&分布, &参数.手指分布信息, 损失类型);
+                    损失函数 += 距离 * finger_distribution_weight;
+                    tiers[itier].finger_distribution = Some(分布);
+                    tiers[itier].finger_distribution_loss = Some(距离);
+                }
+                // 7. 换手率和基准行使用率
+                if let Some(alternation_weight) = tier_weights.alternation {
+                    let pairs = self.tiers_pairs[itier].max(1) as f64;
+                    let alternation = self.tiers_alternation[itier] as f64 / pairs;
+                    损失函数 += alternation * alternation_weight;
+                    tiers[itier].alternation = Some(alternation);
+                }
+                if let Some(home_row_weight) = tier_weights.home_row {
+                    let keys = self.tiers_keys[itier].max(1) as f64;
+                    let home_row = self.tiers_home_row[itier] as f64 / keys;
+                    损失函数 += home_row * home_row_weight;
+                    tiers[itier].home_row = Some(home_row);
+                }
             }
             分组指标.tiers = Some(tiers);
         }
@@ -201,20 +331,24 @@ impl 缓存 {
     pub fn new(
         partial_weights: &部分权重,
         radix: u64,
-        total_count: usize,
-        max_index: u64,
+        tiers_range: Vec<层级范围>,
+        total_characters: i64,
     ) -> Self {
         let total_frequency = 0;
         let total_pairs = 0;
-        let total_extended_pairs = 0;
         // 初始化全局指标的变量
         // 1. 只有加权指标，没有计数指标
         let distribution = vec![0; radix as usize];
         let total_pair_equivalence = 0.0;
-        let total_extended_pair_equivalence = 0.0;
+        let first_key_frequency = vec![0; radix as usize];
+        let last_key_frequency = vec![0; radix as usize];
         // 2. 有加权指标，也有计数指标
         let total_duplication = 0;
+        let total_selection = 0.0;
+        let total_time = 0.0;
         let total_fingering = [0; 8];
+        let total_alternation = 0;
+        let total_home_row = 0;
         let nlevel = partial_weights.levels.as_ref().map_or(0, |v| v.len());
         let total_levels = vec![0; nlevel];
         // 初始化分级指标的变量
@@ -228,26 +362,51 @@ impl 缓存 {
             }
         }
         let tiers_fingering = vec![[0; 8]; ntier];
-        let segment = radix.pow((最大按键组合长度 - 1) as u32);
+        let tiers_pairs = vec![0; ntier];
+        let tiers_pair_equivalence = vec![0.0; ntier];
+        let mut tiers_distribution = vec![];
+        if let Some(tiers) = &partial_weights.tiers {
+            for tier in tiers {
+                let size = if tier.key_distribution.is_some() || tier.finger_distribution.is_some()
+                {
+                    radix as usize
+                } else {
+                    0
+                };
+                tiers_distribution.push(vec![0; size]);
+            }
+        }
+        let tiers_alternation = vec![0; ntier];
+        let tiers_home_row = vec![0; ntier];
+        let tiers_keys = vec![0; ntier];
         let length_breakpoints: Vec<u64> = (0..=8).map(|x| radix.pow(x)).collect();
 
         Self {
             partial_weights: partial_weights.clone(),
-            total_count,
+            tiers_range,
             total_frequency,
             total_pairs,
-            total_extended_pairs,
             distribution,
             total_pair_equivalence,
-            total_extended_pair_equivalence,
+            first_key_frequency,
+            last_key_frequency,
             total_duplication,
+            total_selection,
+            total_time,
+            total_characters,
             total_fingering,
+            total_alternation,
+            total_home_row,
             total_levels,
             tiers_duplication,
             tiers_levels,
             tiers_fingering,
-            max_index,
-            segment,
+            tiers_pairs,
+            tiers_pair_equivalence,
+            tiers_distribution,
+            tiers_alternation,
+            tiers_home_row,
+            tiers_keys,
             length_breakpoints,
             radix,
         }
@@ -255,29 +414,73 @@ impl 缓存 {
 
     /// 用指分布偏差
     /// 计算按键使用率与理想使用率之间的偏差。对于每个按键，偏差是实际频率与理想频率之间的差值乘以一个惩罚系数。用户可以根据自己的喜好自定义理想频率和惩罚系数。
+    /// 偏差可以按绝对值或平方求和，也可以取最大值；KL 散度则衡量实际分布相对理想分布损失的信息量，不使用惩罚系数
     fn 计算键位分布距离(
-        distribution: &Vec<f64>,
-        ideal_distribution: &Vec<键位分布损失函数>,
+        distribution: &[f64],
+        ideal_distribution: &[键位分布损失函数],
+        loss_type: 分布损失,
     ) -> f64 {
-        let mut distance = 0.0;
+        if loss_type == 分布损失::Kl {
+            let total: f64 = ideal_distribution.iter().map(|x| x.理想值).sum();
+            let mut divergence = 0.0;
+            for (frequency, loss) in zip(distribution, ideal_distribution) {
+                if loss.理想值 > 0.0 {
+                    let p = loss.理想值 / total;
+                    divergence += p * (p / frequency.max(1e-9)).ln();
+                }
+            }
+            return divergence;
+        }
+        let mut distance: f64 = 0.0;
         for (frequency, loss) in zip(distribution, ideal_distribution) {
             let diff = frequency - loss.理想值;
-            if diff > 0.0 {
-                distance += loss.高于惩罚 * diff;
+            let penalty = if diff > 0.0 {
+                loss.高于惩罚
             } else {
-                distance -= loss.低于惩罚 * diff;
+                loss.低于惩罚
+            };
+            let deviation = match loss_type {
+                分布损失::Squared => penalty * diff * diff,
+                _ => penalty * diff.abs(),
+            };
+            if loss_type == 分布损失::Max {
+                distance = distance.max(deviation);
+            } else {
+                distance += deviation;
             }
         }
         distance
     }
 
+    /// 词间当量
+    /// 假设相邻两个词的出现相互独立，那么前一个词的末键和后一个词的首键构成的组合的分布就是末键分布和首键分布的乘积，词间当量即为这个分布下组合当量的期望
+    fn 计算词间当量(&self, 参数: &默认目标函数参数) -> f64 {
+        let 末键总频率: i64 = self.last_key_frequency.iter().sum();
+        let 首键总频率: i64 = self.first_key_frequency.iter().sum();
+        if 末键总频率 == 0 || 首键总频率 == 0 {
+            return 0.0;
+        }
+        let mut 总当量 = 0.0;
+        for (末键, 末键频率) in self.last_key_frequency.iter().enumerate() {
+            if *末键频率 == 0 {
+                continue;
+            }
+            let mut 当量 = 0.0;
+            for (首键, 首键频率) in self.first_key_frequency.iter().enumerate() {
+                let 组合 = 末键 + 首键 * self.radix as usize;
+                当量 += *首键频率 as f64 * 参数.当量信息.get(组合).unwrap_or(&0.0);
+            }
+            总当量 += *末键频率 as f64 * 当量;
+        }
+        总当量 / (末键总频率 as f64 * 首键总频率 as f64)
+    }
+
     #[inline(always)]
     pub fn 增减(
         &mut self,
         index: usize,
         frequency: u64,
-        code: 编码,
-        duplicate: bool,
+        (code, duplicate, position): (编码, bool, u8),
         parameters: &默认目标函数参数,
         sign: i64,
     ) {
@@ -293,36 +496,41 @@ impl 缓存 {
         let partial_weights = &self.partial_weights;
         // 一、全局指标
         // 1. 按键分布
-        if partial_weights.key_distribution.is_some() {
+        if partial_weights.key_distribution.is_some()
+            || partial_weights.finger_distribution.is_some()
+            || partial_weights.hand_distribution.is_some()
+        {
             let mut current = code;
             while current > 0 {
                 let key = current % self.radix;
+                current /= self.radix;
+                // 并击方案的编码中可能有补齐用的 0，它不对应任何按键
+                if key == 0 {
+                    continue;
+                }
                 if let Some(x) = self.distribution.get_mut(key as usize) {
                     *x += frequency;
                 }
-                current /= self.radix;
             }
         }
         // 2. 组合当量
         if partial_weights.pair_equivalence.is_some() {
-            let mut code = code;
-            while code > self.radix {
-                let partial_code = (code % self.max_index) as usize;
-                self.total_pair_equivalence += parameters.当量信息[partial_code] * frequency as f64;
-                code /= self.segment;
-            }
+            self.total_pair_equivalence += parameters.编码当量(code) * frequency as f64;
+        }
+        // 3. 词间当量
+        if partial_weights.extended_pair_equivalence.is_some() {
+            let first_key = (code % radix) as usize;
+            let last_key = (code / self.length_breakpoints[length as usize - 1]) as usize;
+            self.first_key_frequency[first_key] += frequency;
+            self.last_key_frequency[last_key] += frequency;
         }
         // 4. 差指法
         if let Some(fingering) = &partial_weights.fingering {
-            let mut code = code;
-            while code > radix {
-                let label = parameters.指法计数[(code % self.max_index) as usize];
-                for (i, weight) in fingering.iter().enumerate() {
-                    if weight.is_some() {
-                        self.total_fingering[i] += frequency * label[i] as i64;
-                    }
+            let label = parameters.编码指法(code);
+            for (i, weight) in fingering.iter().enumerate() {
+                if weight.is_some() {
+                    self.total_fingering[i] += frequency * label[i] as i64;
                 }
-                code /= self.segment;
             }
         }
         // 5. 重码
@@ -337,10 +545,25 @@ impl 缓存 {
                 }
             }
         }
+        // 7. 选择代价
+        if partial_weights.selection.is_some() {
+            self.total_selection += parameters.选择代价[position as usize] * frequency as f64;
+        }
+        // 8. 打字时间
+        if partial_weights.typing_time.is_some() {
+            let last_key = code / self.length_breakpoints[length as usize - 1];
+            self.total_time += parameters.编码时间(code, last_key, position) * frequency as f64;
+        }
+        // 9. 换手率和基准行使用率
+        if partial_weights.alternation.is_some() || partial_weights.home_row.is_some() {
+            let [alternation, home_row] = parameters.编码换手与基准行(code);
+            self.total_alternation += alternation * frequency;
+            self.total_home_row += home_row * frequency;
+        }
         // 二、分级指标
         if let Some(tiers) = &partial_weights.tiers {
             for (itier, tier) in tiers.iter().enumerate() {
-                if index >= tier.top.unwrap_or(self.total_count) {
+                if !self.tiers_range[itier].包含(index) {
                     continue;
                 }
                 // 1. 重码
@@ -357,18 +580,79 @@ impl 缓存 {
                 }
                 // 3. 差指法
                 if let Some(fingering) = &tier.fingering {
-                    let mut code = code;
-                    while code > radix {
-                        let label = parameters.指法计数[(code % self.max_index) as usize];
-                        for (i, weight) in fingering.iter().enumerate() {
-                            if weight.is_some() {
-                                self.tiers_fingering[itier][i] += sign * label[i] as i64;
-                            }
+                    let label = parameters.编码指法(code);
+                    for (i, weight) in fingering.iter().enumerate() {
+                        if weight.is_some() {
+                            self.tiers_fingering[itier][i] += sign * label[i] as i64;
                         }
-                        code /= self.segment;
                     }
                 }
+                // 4. 组合当量，组合数也用于计算换手率
+                if tier.pair_equivalence.is_some() || tier.alternation.is_some() {
+                    self.tiers_pairs[itier] += sign * (length - 1) as i64;
+                }
+                if tier.pair_equivalence.is_some() {
+                    self.tiers_pair_equivalence[itier] += sign as f64 * parameters.编码当量(code);
+                }
+                // 5. 按键分布，也用于汇总手指分布
+                if tier.key_distribution.is_some() || tier.finger_distribution.is_some() {
+                    let mut current = code;
+                    while current > 0 {
+                        let key = current % radix;
+                        current /= radix;
+                        if key == 0 {
+                            continue;
+                        }
+                        if let Some(x) = self.tiers_distribution[itier].get_mut(key as usize) {
+                            *x += sign;
+                        }
+                    }
+                }
+                // 6. 换手率和基准行使用率
+                if tier.alternation.is_some() || tier.home_row.is_some() {
+                    let [alternation, home_row] = parameters.编码换手与基准行(code);
+                    self.tiers_alternation[itier] += sign * alternation;
+                    self.tiers_home_row[itier] += sign * home_row;
+                    self.tiers_keys[itier] += sign * length as i64;
+                }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn 理想分布(理想值: &[f64]) -> Vec<键位分布损失函数> {
+        理想值
+            .iter()
+            .map(|x| 键位分布损失函数 {
+                理想值: *x,
+                低于惩罚: 2.0,
+                高于惩罚: 1.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_key_distribution_loss() {
+        let 距离 = 缓存::计算键位分布距离;
+        let 近似相等 = |a: f64, b: f64| (a - b).abs() < 1e-12;
+        // 第一个键低于理想值 0.25，惩罚为 0.5；第二个键高于理想值 0.25，惩罚为 0.25
+        let 分布 = [0.25, 0.75, 0.0];
+        let 理想 = 理想分布(&[0.5, 0.5, 0.0]);
+        assert!(近似相等(距离(&分布, &理想, 分布损失::L1), 0.75));
+        assert!(近似相等(距离(&分布, &理想, 分布损失::Squared), 0.1875));
+        assert!(近似相等(距离(&分布, &理想, 分布损失::Max), 0.5));
+        // KL 散度先把理想值归一化，不使用惩罚系数，理想值为零的键不参与计算
+        let 理想 = 理想分布(&[2.0, 2.0, 0.0]);
+        let 散度 = 0.5 * (0.5f64 / 0.25).ln() + 0.5 * (0.5f64 / 0.75).ln();
+        assert!(近似相等(距离(&分布, &理想, 分布损失::Kl), 散度));
+        let 散度 = 0.5 * 2f64.ln();
+        assert!(近似相等(距离(&[0.5, 0.25, 0.25], &理想, 分布损失::Kl), 散度));
+        // 实际频率为零时按 1e-9 计算，散度很大但是有限
+        let 散度 = 0.5 * (0.5f64 / 1.0).ln() + 0.5 * (0.5f64 / 1e-9).ln();
+        assert!(近似相等(距离(&[1.0, 0.0, 0.0], &理想, 分布损失::Kl), 散度));
+    }
+}
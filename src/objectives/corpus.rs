@@ -0,0 +1,380 @@
+//! 语料评测
+//!
+//! 用编码器生成的码表对一段真实的文本进行分词，然后模拟逐词输入的过程，统计实际的击键序列上的各项指标。与默认目标函数中按词频加权的指标相比，这里的当量和指法也包括了词与词之间的衔接。
+
+use super::metric::{内置指法数量, 指法名称, 指法标记, FingeringMetric};
+use crate::contexts::default::默认上下文;
+use crate::{原始当量信息, 编码信息};
+use clap::ValueEnum;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// 将语料切分为词的方式
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum 分词方法 {
+    /// 正向最长匹配
+    Longest,
+    /// 按词频计算概率最大的切分
+    Frequency,
+}
+
+#[derive(Debug, Clone)]
+struct 词典项 {
+    按键: Vec<char>,
+    频率: u64,
+    选重: bool,
+    选择键: Option<char>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct 语料指标 {
+    pub characters: usize,
+    pub uncovered_characters: usize,
+    pub words: usize,
+    pub keys: usize,
+    pub keys_per_character: f64,
+    pub pair_equivalence: f64,
+    pub extended_pair_equivalence: f64,
+    pub fingering: FingeringMetric,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fingering_labels: Vec<String>,
+    pub selection_rate: f64,
+    pub select_keys: FxHashMap<char, f64>,
+}
+
+impl Display for 语料指标 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!(
+            "字数：{}；未覆盖：{}；词数：{}；键数：{}；字均码长：{:.4}；",
+            self.characters,
+            self.uncovered_characters,
+            self.words,
+            self.keys,
+            self.keys_per_character
+        ))?;
+        f.write_str(&format!(
+            "组合当量：{:.4}；词间当量：{:.4}；选重率：{:.4}%；",
+            self.pair_equivalence,
+            self.extended_pair_equivalence,
+            self.selection_rate * 100.0
+        ))?;
+        for (index, percent) in self.fingering.iter().enumerate() {
+            if let Some(percent) = percent {
+                f.write_str(&format!(
+                    "{}：{:.2}%；",
+                    指法名称(&self.fingering_labels, index),
+                    percent * 100.0
+                ))?;
+            }
+        }
+        let mut select_keys: Vec<_> = self.select_keys.iter().collect();
+        select_keys.sort_by(|a, b| a.0.cmp(b.0));
+        for (键, 比例) in select_keys {
+            f.write_str(&format!("选择键 {键}：{:.2}%；", 比例 * 100.0))?;
+        }
+        f.write_str("\n")
+    }
+}
+
+pub struct 语料评测器<'a> {
+    上下文: &'a 默认上下文,
+    原始当量信息: &'a 原始当量信息,
+    词典: FxHashMap<String, 词典项>,
+    最大词长: usize,
+    总频率: f64,
+}
+
+impl<'a> 语料评测器<'a> {
+    /// 用编码器的输出构建词典，每个词取其简码对应的实际编码，也就是包括选择键在内的完整击键序列
+    pub fn 新建(
+        上下文: &'a 默认上下文,
+        编码结果: &[编码信息],
+        原始当量信息: &'a 原始当量信息,
+    ) -> Self {
+        let mut 词典: FxHashMap<String, 词典项> = FxHashMap::default();
+        for (可编码对象, 编码信息) in 上下文.词列表.iter().zip(编码结果) {
+            if 可编码对象.容错 {
+                continue;
+            }
+            let 部分编码信息 = if 编码信息.简码.原始编码 != 0 {
+                &编码信息.简码
+            } else {
+                &编码信息.全码
+            };
+            let 按键 = 上下文.棱镜.数字转编码(部分编码信息.实际编码);
+            // 实际编码与原始编码不同，说明末尾加上了选择键
+            let 选择键 = (部分编码信息.实际编码 != 部分编码信息.原始编码)
+                .then(|| 按键.last().copied())
+                .flatten();
+            let 项 = 词典项 {
+                按键,
+                频率: 可编码对象.频率,
+                选重: 部分编码信息.原始编码候选位置 > 0,
+                选择键,
+            };
+            // 多音字等重复出现的词取频率最高的一项
+            match 词典.get(&可编码对象.词) {
+                Some(已有) if 已有.频率 >= 项.频率 => {}
+                _ => {
+                    词典.insert(可编码对象.词.clone(), 项);
+                }
+            }
+        }
+        let 最大词长 = 词典.keys().map(|x| x.chars().count()).max().unwrap_or(1);
+        let 总频率 = 词典.values().map(|x| x.频率 as f64).sum::<f64>().max(1.0);
+        Self {
+            上下文,
+            原始当量信息,
+            词典,
+            最大词长,
+            总频率,
+        }
+    }
+
+    /// 将一段不含未覆盖字符的文本切分为词
+    fn 分词(&self, 文本: &[char], 方法: 分词方法) -> Vec<&词典项> {
+        let 查找 = |i: usize, j: usize| -> Option<&词典项> {
+            self.词典.get(&文本[i..j].iter().collect::<String>())
+        };
+        match 方法 {
+            分词方法::Longest => {
+                let mut 结果 = vec![];
+                let mut i = 0;
+                while i < 文本.len() {
+                    let 上限 = (i + self.最大词长).min(文本.len());
+                    let (j, 项) = (i + 1..=上限)
+                        .rev()
+                        .find_map(|j| 查找(i, j).map(|x| (j, x)))
+                        .unwrap();
+                    结果.push(项);
+                    i = j;
+                }
+                结果
+            }
+            分词方法::Frequency => {
+                // 最短路：每个词的代价为其概率的负对数
+                let n = 文本.len();
+                let mut 代价 = vec![f64::INFINITY; n + 1];
+                let mut 来源: Vec<Option<(usize, &词典项)>> = vec![None; n + 1];
+                代价[0] = 0.0;
+                for i in 0..n {
+                    if 代价[i].is_infinite() {
+                        continue;
+                    }
+                    for j in i + 1..=(i + self.最大词长).min(n) {
+                        if let Some(项) = 查找(i, j) {
+                            let 概率 = (项.频率 as f64).max(1.0) / self.总频率;
+                            let 新代价 = 代价[i] - 概率.ln();
+                            if 新代价 < 代价[j] {
+                                代价[j] = 新代价;
+                                来源[j] = Some((i, 项));
+                            }
+                        }
+                    }
+                }
+                let mut 结果 = vec![];
+                let mut j = n;
+                while let Some((i, 项)) = 来源[j] {
+                    结果.push(项);
+                    j = i;
+                }
+                结果.reverse();
+                结果
+            }
+        }
+    }
+
+    fn 计算当量(&self, 按键: &[char]) -> (f64, usize) {
+        let mut 当量 = 0.0;
+        for 长度 in [2, 3, 4] {
+            for 组合 in 按键.windows(长度) {
+                let 组合: String = 组合.iter().collect();
+                当量 += self.原始当量信息.get(&组合).unwrap_or(&0.0);
+            }
+        }
+        (当量, 按键.len().saturating_sub(1))
+    }
+
+    /// 评测一段语料。不在码表中的字符会打断击键序列，只计入未覆盖字数
+    pub fn 评测(&self, 语料: &str, 方法: 分词方法) -> 语料指标 {
+        let 指法标记 = 指法标记::new(&self.上下文.键盘几何);
+        let 自定义指法 = &self.上下文.自定义指法;
+        let mut 指标 = 语料指标 {
+            characters: 0,
+            uncovered_characters: 0,
+            words: 0,
+            keys: 0,
+            keys_per_character: 0.0,
+            pair_equivalence: 0.0,
+            extended_pair_equivalence: 0.0,
+            fingering: FingeringMetric::default(),
+            fingering_labels: 自定义指法.iter().map(|x| x.名称.clone()).collect(),
+            selection_rate: 0.0,
+            select_keys: FxHashMap::default(),
+        };
+        let mut 总当量 = 0.0;
+        let mut 总组合数 = 0;
+        let mut 词间当量 = 0.0;
+        let mut 词间组合数 = 0;
+        let mut 指法计数 = [0_u64; 8];
+        let mut 选重次数 = 0;
+        let mut 选择键计数: FxHashMap<char, usize> = FxHashMap::default();
+        let 字符列表: Vec<char> = 语料.chars().collect();
+        let mut 片段开始 = 0;
+        for i in 0..=字符列表.len() {
+            let 已覆盖 = 字符列表
+                .get(i)
+                .is_some_and(|c| self.词典.contains_key(&c.to_string()));
+            if 已覆盖 {
+                continue;
+            }
+            if let Some(c) = 字符列表.get(i) {
+                if !c.is_whitespace() {
+                    指标.uncovered_characters += 1;
+                }
+            }
+            let 片段 = &字符列表[片段开始..i];
+            片段开始 = i + 1;
+            if 片段.is_empty() {
+                continue;
+            }
+            指标.characters += 片段.len();
+            let mut 击键序列: Vec<char> = vec![];
+            for 项 in self.分词(片段, 方法) {
+                指标.words += 1;
+                if 项.选重 {
+                    选重次数 += 1;
+                }
+                if let Some(键) = 项.选择键 {
+                    *选择键计数.entry(键).or_default() += 1;
+                }
+                if let (Some(末键), Some(首键)) = (击键序列.last(), 项.按键.first()) {
+                    let 衔接: String = [*末键, *首键].iter().collect();
+                    词间当量 += self.原始当量信息.get(&衔接).unwrap_or(&0.0);
+                    词间组合数 += 1;
+                }
+                击键序列.extend(&项.按键);
+            }
+            指标.keys += 击键序列.len();
+            let (当量, 组合数) = self.计算当量(&击键序列);
+            总当量 += 当量;
+            总组合数 += 组合数;
+            // 一段语料的按键可能很长，直接累加到 u64 上，以免指法向量溢出
+            指法标记.累加指法计数(&击键序列, 自定义指法, &mut 指法计数);
+        }
+        let 比例 = |x: f64, y: usize| if y == 0 { 0.0 } else { x / y as f64 };
+        指标.keys_per_character = 比例(指标.keys as f64, 指标.characters);
+        指标.pair_equivalence = 比例(总当量, 总组合数);
+        指标.extended_pair_equivalence = 比例(词间当量, 词间组合数);
+        for (i, 计数) in 指法计数.iter().enumerate() {
+            if i < 内置指法数量 + 自定义指法.len() {
+                指标.fingering[i] = Some(比例(*计数 as f64, 总组合数));
+            }
+        }
+        指标.selection_rate = 比例(选重次数 as f64, 指标.words);
+        指标.select_keys = 选择键计数
+            .into_iter()
+            .map(|(k, v)| (k, 比例(v as f64, 指标.words)))
+            .collect();
+        指标
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoders::default::默认编码器;
+    use crate::encoders::编码器;
+    use crate::interfaces::默认输入;
+    use crate::原始可编码对象;
+
+    const 测试配置: &str = r#"
+form:
+  alphabet: asdfjkl
+  mapping: {口: a, 日: s, 木: d, 水: f, 火: j, 土: k}
+encoder:
+  max_length: 2
+  select_keys: ["_"]
+"#;
+
+    fn 词(词: &str, 元素序列: &str, 频率: u64) -> 原始可编码对象 {
+        原始可编码对象 {
+            词: 词.to_string(),
+            元素序列: 元素序列.to_string(),
+            频率,
+            简码长度: u64::MAX,
+            固定排名: None,
+        }
+    }
+
+    fn 新建上下文() -> (默认上下文, Vec<编码信息>) {
+        let 词列表 = vec![
+            词("一", "口 日", 1000),
+            词("二", "木 水", 1000),
+            词("三", "火 土", 1000),
+            词("一二", "口 木", 1),
+            词("二三", "木 火", 1000),
+        ];
+        let 上下文 = 默认上下文::新建(默认输入 {
+            配置: serde_yaml::from_str(测试配置).unwrap(),
+            词列表,
+            ..Default::default()
+        })
+        .unwrap();
+        let mut 编码器 = 默认编码器::新建(&上下文).unwrap();
+        let mut 编码结果: Vec<_> = 上下文.词列表.iter().map(编码信息::new).collect();
+        编码器.编码(&上下文.初始决策, &None, &mut 编码结果);
+        (上下文, 编码结果)
+    }
+
+    fn 当量() -> 原始当量信息 {
+        [
+            ("as", 1.0),
+            ("sd", 2.0),
+            ("jk", 5.0),
+            ("ad", 6.0),
+            ("dj", 7.0),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), *v))
+        .collect()
+    }
+
+    #[test]
+    fn test_corpus_segmentation() {
+        let (上下文, 编码结果) = 新建上下文();
+        let 当量 = 当量();
+        let 评测器 = 语料评测器::新建(&上下文, &编码结果, &当量);
+        // 最长匹配切分为「一二 三」和「一」，击键为 adjk 和 as，逗号打断了击键序列
+        let 指标 = 评测器.评测("一二三，一", 分词方法::Longest);
+        assert_eq!(指标.characters, 4);
+        assert_eq!(指标.uncovered_characters, 1);
+        assert_eq!(指标.words, 3);
+        assert_eq!(指标.keys, 6);
+        assert_eq!(指标.keys_per_character, 1.5);
+        assert_eq!(指标.pair_equivalence, (6.0 + 7.0 + 5.0 + 1.0) / 4.0);
+        assert_eq!(指标.extended_pair_equivalence, 7.0);
+        // 按词频切分时「一二」的频率太低，切分为「一 二三」，击键为 asdj
+        let 指标 = 评测器.评测("一二三", 分词方法::Frequency);
+        assert_eq!(指标.words, 2);
+        assert_eq!(指标.keys, 4);
+        assert_eq!(指标.pair_equivalence, (1.0 + 2.0 + 7.0) / 3.0);
+        assert_eq!(指标.extended_pair_equivalence, 2.0);
+        assert_eq!(指标.selection_rate, 0.0);
+    }
+
+    #[test]
+    fn test_corpus_long_run() {
+        let (上下文, 编码结果) = 新建上下文();
+        let 当量 = 当量();
+        let 评测器 = 语料评测器::新建(&上下文, &编码结果, &当量);
+        // 400 个按键都在左手，同手组合远远超过 u8 的范围
+        let 语料 = "一".repeat(200);
+        let 指标 = 评测器.评测(&语料, 分词方法::Longest);
+        assert_eq!(指标.keys, 400);
+        assert_eq!(指标.fingering[0], Some(1.0));
+        assert_eq!(指标.pair_equivalence, 200.0 / 399.0);
+        assert_eq!(指标.extended_pair_equivalence, 0.0);
+    }
+}
@@ -1,13 +1,16 @@
+use indexmap::IndexMap;
 use rustc_hash::FxHashMap;
 
 use super::cache::缓存;
+use super::expression::目标函数表达式;
+use super::learnability::易学性评估器;
 use super::metric::默认指标;
 use super::目标函数;
-use crate::config::部分权重;
-use crate::contexts::default::{默认上下文, 默认决策, 默认决策空间};
+use crate::config::{元素亲和, 约束模式, 部分权重};
+use crate::contexts::default::{默认上下文, 默认决策, 默认决策空间, 默认安排};
 use crate::encoders::编码器;
 use crate::错误;
-use crate::{元素, 指法向量, 编码信息, 键位分布损失函数};
+use crate::{元素, 指法向量, 编码, 编码信息, 键位分布损失函数};
 
 #[derive(Clone)]
 pub struct 默认目标函数<E: 编码器> {
@@ -16,6 +19,214 @@ pub struct 默认目标函数<E: 编码器> {
     pub 编码器: E,
     pub 编码结果: Vec<编码信息>,
     pub 计数桶列表: Vec<[Option<缓存>; 2]>,
+    pub 表达式: Option<目标函数表达式>,
+    pub 约束: Option<约束求解状态>,
+    pub 易学性: Option<易学性评估器>,
+    pub 亲和: Option<亲和状态>,
+}
+
+#[derive(Clone)]
+pub struct 约束条件 {
+    pub 指标: 目标函数表达式,
+    pub 下限: Option<f64>,
+    pub 上限: Option<f64>,
+}
+
+/// 惩罚模式下，每经过这么多次评测，根据这段时间内的解是否可行调整一次惩罚系数
+const 惩罚调整间隔: usize = 100;
+
+#[derive(Clone)]
+pub struct 约束求解状态 {
+    pub 条件列表: Vec<约束条件>,
+    pub 模式: 约束模式,
+    pub 初始惩罚系数: f64,
+    pub 惩罚系数: f64,
+    pub 评测次数: usize,
+    pub 可行次数: usize,
+}
+
+impl 约束求解状态 {
+    fn 计算违反量(&self, 指标: &默认指标, 损失: f64) -> f64 {
+        let mut 违反量 = 0.0;
+        for 条件 in &self.条件列表 {
+            let 值 = 条件.指标.求值(指标, 损失);
+            if let Some(上限) = 条件.上限 {
+                违反量 += (值 - 上限).max(0.0);
+            }
+            if let Some(下限) = 条件.下限 {
+                违反量 += (下限 - 值).max(0.0);
+            }
+        }
+        违反量
+    }
+
+    /// 惩罚模式下，如果一段时间内的解全都不可行，就加大惩罚系数；全都可行，就减小惩罚系数，但不低于初始值
+    fn 记录(&mut self, 违反量: f64) {
+        if self.模式 != 约束模式::Penalty {
+            return;
+        }
+        self.评测次数 += 1;
+        if 违反量 == 0.0 {
+            self.可行次数 += 1;
+        }
+        if self.评测次数 == 惩罚调整间隔 {
+            if self.可行次数 == 0 {
+                self.惩罚系数 *= 2.0;
+            } else if self.可行次数 == self.评测次数 {
+                self.惩罚系数 = (self.惩罚系数 / 2.0).max(self.初始惩罚系数);
+            }
+            self.评测次数 = 0;
+            self.可行次数 = 0;
+        }
+    }
+
+    /// 比较两个解的（损失, 违反量），负数表示甲更优
+    ///
+    /// 字典序模式下先比较违反量，违反量相同时才比较损失；惩罚模式下两个解都按当前的惩罚系数计分，惩罚系数调整之后，之前记录的解也按新的系数比较
+    ///
+    /// 字典序模式下违反量不同时返回的是违反量之差，与损失之差不在同一尺度上，也不做换算：退火总是接受违反量减小的移动，违反量增大的移动按违反量之差和温度决定接受的概率，与损失无关。退火的温度按差值的平均大小标定，混合了两种尺度，所以当违反量与损失的量级相差很大时，需要用 penalty 模式并调整惩罚系数
+    fn 差值(&self, 甲: (f64, f64), 乙: (f64, f64)) -> f64 {
+        let ((甲损失, 甲违反量), (乙损失, 乙违反量)) = (甲, 乙);
+        match self.模式 {
+            约束模式::Lexicographic if 甲违反量 != 乙违反量 => 甲违反量 - 乙违反量,
+            约束模式::Lexicographic => 甲损失 - 乙损失,
+            约束模式::Penalty => 甲损失 - 乙损失 + self.惩罚系数 * (甲违反量 - 乙违反量),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct 亲和项 {
+    pub 元素: [元素; 2],
+    pub 同键: bool,
+    pub 分数: f64,
+}
+
+/// 元素亲和项的增量计算状态
+///
+/// 元素的第一码可能通过引用或者归并取自其他元素，所以一个元素的安排变化时，下游元素所在的亲和项也要重新计算
+#[derive(Clone)]
+pub struct 亲和状态 {
+    pub 项列表: Vec<亲和项>,
+    pub 相关项: Vec<Vec<usize>>,
+    pub 下游: Vec<Vec<元素>>,
+    pub 当前分数: Vec<f64>,
+    pub 总分: f64,
+    pub 进制: usize,
+}
+
+impl 亲和状态 {
+    fn 新建(上下文: &默认上下文, 配置: &[元素亲和]) -> Result<Self, 错误> {
+        let 棱镜 = &上下文.棱镜;
+        let 元素数 = 上下文.初始决策.元素.len();
+        let mut 项列表 = vec![];
+        let mut 相关项 = vec![vec![]; 元素数];
+        for 亲和 in 配置 {
+            let mut 元素 = [0; 2];
+            for (i, 名称) in 亲和.elements.iter().enumerate() {
+                元素[i] = *棱镜
+                    .元素转数字
+                    .get(名称)
+                    .ok_or(format!("亲和关系中的元素 {名称} 不存在"))?;
+                相关项[元素[i]].push(项列表.len());
+            }
+            项列表.push(亲和项 {
+                元素,
+                同键: 亲和.same_key.unwrap_or(true),
+                分数: 亲和.score,
+            });
+        }
+        let mut 下游 = vec![vec![]; 元素数];
+        for (序号, 条件安排列表) in 上下文.决策空间.元素.iter().enumerate() {
+            let 候选安排 = 条件安排列表
+                .iter()
+                .map(|x| &x.安排)
+                .chain([&上下文.初始决策.元素[序号]]);
+            for 安排 in 候选安排 {
+                let 上游列表 = match 安排 {
+                    默认安排::归并(上游) => vec![*上游],
+                    默认安排::键位(列表) => 列表.iter().map(|x| x.0).collect(),
+                    默认安排::未选取 => vec![],
+                };
+                for 上游 in 上游列表 {
+                    if 上游 >= 棱镜.进制 as usize && !下游[上游].contains(&序号) {
+                        下游[上游].push(序号);
+                    }
+                }
+            }
+        }
+        Ok(Self {
+            当前分数: vec![0.0; 项列表.len()],
+            项列表,
+            相关项,
+            下游,
+            总分: 0.0,
+            进制: 棱镜.进制 as usize,
+        })
+    }
+
+    /// 元素在某个码位上的键，未选取的元素为 0
+    fn 码位(&self, 决策: &默认决策, 元素: 元素, 位置: usize) -> usize {
+        if 元素 < self.进制 {
+            return 元素;
+        }
+        match &决策.元素[元素] {
+            默认安排::归并(上游) => self.码位(决策, *上游, 位置),
+            默认安排::键位(列表) => {
+                let (上游, 上游位置) = 列表[位置];
+                if 上游 == 0 {
+                    0
+                } else {
+                    self.码位(决策, 上游, 上游位置)
+                }
+            }
+            默认安排::未选取 => 0,
+        }
+    }
+
+    fn 计算项(&self, 决策: &默认决策, 项: &亲和项) -> f64 {
+        let 甲 = self.码位(决策, 项.元素[0], 0);
+        let 乙 = self.码位(决策, 项.元素[1], 0);
+        if 甲 == 0 || 乙 == 0 || (甲 == 乙) != 项.同键 {
+            0.0
+        } else {
+            项.分数
+        }
+    }
+
+    /// 没有变化信息时重新计算所有亲和项，否则只计算变化的元素及其下游元素所在的项
+    fn 计算(&mut self, 决策: &默认决策, 变化: &Option<Vec<元素>>) -> f64 {
+        let Some(变化) = 变化 else {
+            for (序号, 项) in self.项列表.iter().enumerate() {
+                self.当前分数[序号] = self.计算项(决策, 项);
+            }
+            self.总分 = self.当前分数.iter().sum();
+            return self.总分;
+        };
+        let mut 受影响元素 = 变化.clone();
+        let mut 序号 = 0;
+        while 序号 < 受影响元素.len() {
+            for 下游 in &self.下游[受影响元素[序号]] {
+                if !受影响元素.contains(下游) {
+                    受影响元素.push(*下游);
+                }
+            }
+            序号 += 1;
+        }
+        let mut 受影响项: Vec<_> = 受影响元素
+            .iter()
+            .flat_map(|x| &self.相关项[*x])
+            .copied()
+            .collect();
+        受影响项.sort_unstable();
+        受影响项.dedup();
+        for 序号 in 受影响项 {
+            let 分数 = self.计算项(决策, &self.项列表[序号]);
+            self.总分 += 分数 - self.当前分数[序号];
+            self.当前分数[序号] = 分数;
+        }
+        self.总分
+    }
 }
 
 #[derive(Clone)]
@@ -23,8 +234,113 @@ pub struct 默认目标函数参数 {
     pub 键位分布信息: Vec<键位分布损失函数>,
     pub 当量信息: Vec<f64>,
     pub 指法计数: Vec<指法向量>,
+    pub 当量尾部: Vec<f64>,
+    pub 指法尾部: Vec<指法向量>,
+    pub 进制: u64,
     pub 数字转键: FxHashMap<u64, char>,
     pub 正则化强度: f64,
+    pub 自定义指法名称: Vec<String>,
+    pub 按键手指: Vec<Option<usize>>,
+    pub 手指分布信息: Vec<键位分布损失函数>,
+    pub 左右手分布信息: Vec<键位分布损失函数>,
+    pub 选择代价: Vec<f64>,
+    pub 时间信息: Vec<f64>,
+    pub 时间尾部: Vec<f64>,
+    pub 选重开销: f64,
+    pub 自动上屏开销: f64,
+    /// 按键是否为选择键，以按键的数字表示为下标
+    pub 是选择键: Vec<bool>,
+    pub 换手与基准行: Vec<[i64; 2]>,
+    pub 换手与基准行尾部: Vec<[i64; 2]>,
+    /// 并击方案中一个并击所占的编码范围，此时当量信息按并击查表，而不是滑动窗口
+    pub 并击乘数: Option<u64>,
+}
+
+impl 默认目标函数参数 {
+    /// 长度不超过预处理长度的编码直接查表；更长的编码每次向后滑动一个按键，加上以新按键结尾的那些组合的当量
+    #[inline(always)]
+    pub fn 编码当量(&self, 编码: 编码) -> f64 {
+        if let Some(并击乘数) = self.并击乘数 {
+            let mut 当量 = 0.0;
+            let mut 编码 = 编码;
+            while 编码 > 0 {
+                当量 += self.当量信息[(编码 % 并击乘数) as usize];
+                编码 /= 并击乘数;
+            }
+            return 当量;
+        }
+        let 最大编码 = self.当量信息.len() as u64;
+        let mut 当量 = self.当量信息[(编码 % 最大编码) as usize];
+        let mut 编码 = 编码;
+        while 编码 >= 最大编码 {
+            编码 /= self.进制;
+            当量 += self.当量尾部[(编码 % 最大编码) as usize];
+        }
+        当量
+    }
+
+    /// 与编码当量相同，用滑动窗口计算任意长度编码的打字时间，再加上选重和自动上屏的开销
+    #[inline(always)]
+    pub fn 编码时间(&self, 编码: 编码, 末键: u64, 候选位置: u8) -> f64 {
+        let 最大编码 = self.时间信息.len() as u64;
+        let mut 时间 = self.时间信息[(编码 % 最大编码) as usize];
+        let mut 剩余 = 编码;
+        while 剩余 >= 最大编码 {
+            剩余 /= self.进制;
+            时间 += self.时间尾部[(剩余 % 最大编码) as usize];
+        }
+        if 候选位置 > 0 {
+            时间 += self.选重开销;
+        }
+        if !self.是选择键[末键 as usize] {
+            时间 += self.自动上屏开销;
+        }
+        时间
+    }
+
+    /// 与编码当量相同，用滑动窗口统计任意长度编码的换手组合数和基准行按键数
+    #[inline(always)]
+    pub fn 编码换手与基准行(&self, 编码: 编码) -> [i64; 2] {
+        let 最大编码 = self.换手与基准行.len() as u64;
+        let [mut 换手, mut 基准行] = self.换手与基准行[(编码 % 最大编码) as usize];
+        let mut 编码 = 编码;
+        while 编码 >= 最大编码 {
+            编码 /= self.进制;
+            let [甲, 乙] = self.换手与基准行尾部[(编码 % 最大编码) as usize];
+            换手 += 甲;
+            基准行 += 乙;
+        }
+        [换手, 基准行]
+    }
+
+    /// 与编码当量相同，用滑动窗口统计任意长度编码的指法
+    #[inline(always)]
+    pub fn 编码指法(&self, 编码: 编码) -> 指法向量 {
+        let 最大编码 = self.指法计数.len() as u64;
+        let mut 指法 = self.指法计数[(编码 % 最大编码) as usize];
+        let mut 编码 = 编码;
+        while 编码 >= 最大编码 {
+            编码 /= self.进制;
+            let 尾部 = self.指法尾部[(编码 % 最大编码) as usize];
+            for (x, y) in 指法.iter_mut().zip(尾部) {
+                *x += y;
+            }
+        }
+        指法
+    }
+}
+
+/// 对于预处理表中的每个编码，计算它的值与去掉最后一个按键之后的值的差，也就是以最后一个按键结尾的那些组合的贡献
+fn 计算尾部<T: Copy>(表: &[T], 进制: u64, 差: impl Fn(T, T) -> T) -> Vec<T> {
+    (0..表.len() as u64)
+        .map(|编码| {
+            let mut 前缀乘数 = 1;
+            while 前缀乘数 * 进制 <= 编码 {
+                前缀乘数 *= 进制;
+            }
+            差(表[编码 as usize], 表[(编码 % 前缀乘数) as usize])
+        })
+        .collect()
 }
 
 pub type Frequencies = Vec<f64>;
@@ -48,28 +364,170 @@ impl<E: 编码器<决策 = 默认决策>> 默认目标函数<E> {
     pub fn 新建(上下文: &默认上下文, 编码器: E) -> Result<Self, 错误> {
         let 键位分布信息 = 上下文.键位分布信息.clone();
         let 当量信息 = 上下文.当量信息.clone();
-        let 指法计数 = 上下文.棱镜.预处理指法标记(上下文.get_space());
-        let config = 上下文
+        let 指法计数 = 上下文.棱镜.预处理指法标记(
+            上下文.get_space(),
+            &上下文.键盘几何,
+            &上下文.自定义指法,
+        );
+        let mut config = 上下文
             .配置
             .optimization
             .as_ref()
             .ok_or("优化配置不存在")?
             .objective
             .clone();
-        let 最大编码 = 当量信息.len() as u64;
-        let 构造缓存 =
-            |x: &部分权重| 缓存::new(x, 上下文.棱镜.进制, 上下文.词列表.len(), 最大编码);
-        let 一字全码 = config.characters_full.as_ref().map(构造缓存);
-        let 一字简码 = config.characters_short.as_ref().map(构造缓存);
-        let 多字全码 = config.words_full.as_ref().map(构造缓存);
-        let 多字简码 = config.words_short.as_ref().map(构造缓存);
+        // 约束中的指标不需要配置权重，没有权重的指标以零权重计算
+        let mut 条件列表 = vec![];
+        for 约束 in config.constraints.clone().iter().flatten() {
+            let 指标 = 目标函数表达式::解析(&约束.metric)?;
+            指标.补充权重(&mut config)?;
+            条件列表.push(约束条件 {
+                指标,
+                下限: 约束.min,
+                上限: 约束.max,
+            });
+        }
+        let 按键手指: Vec<_> = (0..上下文.棱镜.进制)
+            .map(|键| {
+                let 键 = 上下文.棱镜.数字转键.get(&键)?;
+                上下文.键盘几何.按键.get(键).map(|x| x.手指编号())
+            })
+            .collect();
+        let 手指分布信息 =
+            Self::汇总理想分布(&config.finger_targets, &键位分布信息, &按键手指, 10)?;
+        let 按键左右手: Vec<_> = 按键手指.iter().map(|x| x.map(|x| x / 5)).collect();
+        let 左右手分布信息 =
+            Self::汇总理想分布(&config.hand_targets, &键位分布信息, &按键左右手, 2)?;
+        let 进制 = 上下文.棱镜.进制;
+        let 当量尾部 = 计算尾部(&当量信息, 进制, |x, y| x - y);
+        let 指法尾部 = 计算尾部(&指法计数, 进制, |x, y| {
+            let mut 差 = x;
+            for (a, b) in 差.iter_mut().zip(y) {
+                *a -= b;
+            }
+            差
+        });
+        let 选择键 = 上下文.配置.encoder.select_keys.clone().unwrap_or(vec!['_']);
+        let 选择代价 = Self::计算选择代价(&选择键, &config.selection_costs)?;
+        let 部分权重列表 = [
+            &config.characters_full,
+            &config.characters_short,
+            &config.words_full,
+            &config.words_short,
+        ];
+        let 使用时间模型 = 部分权重列表
+            .iter()
+            .any(|x| x.as_ref().is_some_and(|x| x.typing_time.is_some()));
+        // 并击中的按键同时按下，没有先后顺序，依赖按键顺序的指标都无法按并击计算
+        if 上下文.配置.encoder.chord.is_some() {
+            for 部分 in 部分权重列表.iter().copied().flatten() {
+                let 层级 = || 部分.tiers.iter().flatten();
+                let 顺序指标 = [
+                    (
+                        "extended_pair_equivalence",
+                        部分.extended_pair_equivalence.is_some(),
+                    ),
+                    (
+                        "fingering",
+                        部分.fingering.is_some() || 层级().any(|x| x.fingering.is_some()),
+                    ),
+                    ("typing_time", 部分.typing_time.is_some()),
+                    (
+                        "alternation",
+                        部分.alternation.is_some() || 层级().any(|x| x.alternation.is_some()),
+                    ),
+                ];
+                if let Some((名称, _)) = 顺序指标.iter().find(|x| x.1) {
+                    return Err(format!("并击方案不支持 {名称}").into());
+                }
+            }
+        }
+        let (时间信息, 选重开销, 自动上屏开销) = match &上下文.时间模型 {
+            Some(模型) => (
+                上下文
+                    .棱镜
+                    .预处理时间信息(模型, 指法计数.len(), &上下文.键盘几何),
+                模型.选重开销,
+                模型.自动上屏开销,
+            ),
+            None if 使用时间模型 => {
+                return Err("目标函数中使用了 typing_time，但没有提供时间模型".into());
+            }
+            None => (vec![0.0; 指法计数.len()], 0.0, 0.0),
+        };
+        let 时间尾部 = 计算尾部(&时间信息, 进制, |x, y| x - y);
+        let 换手与基准行 = 上下文
+            .棱镜
+            .预处理换手与基准行(指法计数.len(), &上下文.键盘几何);
+        let 换手与基准行尾部 = 计算尾部(&换手与基准行, 进制, |x, y| {
+            [x[0] - y[0], x[1] - y[1]]
+        });
+        let mut 是选择键 = vec![false; 进制 as usize];
+        for 键 in &上下文.选择键 {
+            是选择键[*键 as usize] = true;
+        }
+        let mut 总字数 = [0, 0];
+        for 词 in &上下文.词列表 {
+            let 桶索引 = if 词.词长 == 1 { 0 } else { 1 };
+            总字数[桶索引] += (词.频率 * 词.词长 as u64) as i64;
+        }
+        let 构造缓存 = |x: &部分权重, 桶索引: usize, 全简索引: usize| {
+            let 层级范围 = 上下文.层级范围[桶索引][全简索引].clone();
+            缓存::new(x, 进制, 层级范围, 总字数[桶索引])
+        };
+        let 一字全码 = config.characters_full.as_ref().map(|x| 构造缓存(x, 0, 0));
+        let 一字简码 = config.characters_short.as_ref().map(|x| 构造缓存(x, 0, 1));
+        let 多字全码 = config.words_full.as_ref().map(|x| 构造缓存(x, 1, 0));
+        let 多字简码 = config.words_short.as_ref().map(|x| 构造缓存(x, 1, 1));
         let 计数桶列表 = vec![[一字全码, 一字简码], [多字全码, 多字简码]];
         let 参数 = 默认目标函数参数 {
             键位分布信息,
             当量信息,
             指法计数,
+            当量尾部,
+            指法尾部,
+            进制,
             数字转键: 上下文.棱镜.数字转键.clone(),
             正则化强度: config.regularization_strength.unwrap_or(1.0),
+            自定义指法名称: 上下文.自定义指法.iter().map(|x| x.名称.clone()).collect(),
+            按键手指,
+            手指分布信息,
+            左右手分布信息,
+            选择代价,
+            时间信息,
+            时间尾部,
+            选重开销,
+            自动上屏开销,
+            是选择键,
+            换手与基准行,
+            换手与基准行尾部,
+            并击乘数: 上下文
+                .配置
+                .encoder
+                .chord
+                .as_ref()
+                .map(|x| 进制.pow(x.size as u32)),
+        };
+        let 表达式 = match &config.expression {
+            Some(源) => Some(目标函数表达式::新建(源, &config)?),
+            None => None,
+        };
+        let 初始惩罚系数 = config.constraint_penalty.unwrap_or(1.0);
+        let 约束 = (!条件列表.is_empty()).then(|| 约束求解状态 {
+            条件列表,
+            模式: config.constraint_mode.unwrap_or_default(),
+            初始惩罚系数,
+            惩罚系数: 初始惩罚系数,
+            评测次数: 0,
+            可行次数: 0,
+        });
+        let 易学性 = match &config.learnability {
+            Some(配置) => Some(易学性评估器::新建(上下文, 配置)?),
+            None => None,
+        };
+        let 亲和 = match &上下文.配置.form.mapping_affinities {
+            Some(配置) if !配置.is_empty() => Some(亲和状态::新建(上下文, 配置)?),
+            _ => None,
         };
         let 编码结果: Vec<_> = 上下文.词列表.iter().map(编码信息::new).collect();
         Ok(Self {
@@ -78,9 +536,79 @@ impl<E: 编码器<决策 = 默认决策>> 默认目标函数<E> {
             编码结果: 编码结果.clone(),
             计数桶列表: 计数桶列表.clone(),
             决策空间: 上下文.决策空间.clone(),
+            表达式,
+            约束,
+            易学性,
+            亲和,
         })
     }
 
+    /// 得到按手指或按左右手汇总的理想分布。配置中没有给出时，将各个按键的理想值按所属的手指或左右手相加
+    fn 汇总理想分布(
+        目标: &Option<Vec<f64>>,
+        键位分布信息: &[键位分布损失函数],
+        分组: &[Option<usize>],
+        分组数: usize,
+    ) -> Result<Vec<键位分布损失函数>, 错误> {
+        let mut 理想值 = match 目标 {
+            Some(目标) if 目标.len() != 分组数 => {
+                return Err(
+                    format!("理想分布应有 {分组数} 个值，但实际有 {} 个", 目标.len()).into(),
+                );
+            }
+            Some(目标) => 目标.clone(),
+            None => {
+                let mut 理想值 = vec![0.0; 分组数];
+                for (损失函数, 组) in 键位分布信息.iter().zip(分组) {
+                    if let Some(组) = 组 {
+                        理想值[*组] += 损失函数.理想值;
+                    }
+                }
+                理想值
+            }
+        };
+        // 实际分布是比例，所以理想分布也按总和归一化，配置中可以直接写百分数
+        let 总和: f64 = 理想值.iter().sum();
+        if 总和 > 0.0 {
+            理想值.iter_mut().for_each(|x| *x /= 总和);
+        }
+        Ok(理想值
+            .into_iter()
+            .map(|理想值| 键位分布损失函数 {
+                理想值,
+                低于惩罚: 1.0,
+                高于惩罚: 1.0,
+            })
+            .collect())
+    }
+
+    /// 按候选位置列出选择代价。候选位置超出选择键数量的词仍用最后一个选择键的代价计算
+    fn 计算选择代价(
+        选择键: &[char],
+        代价: &Option<IndexMap<char, f64>>,
+    ) -> Result<Vec<f64>, 错误> {
+        if let Some(代价) = 代价 {
+            if let Some(键) = 代价.keys().find(|x| !选择键.contains(x)) {
+                return Err(format!("选择代价中的 {键} 不是选择键").into());
+            }
+        }
+        let 列表: Vec<_> = 选择键
+            .iter()
+            .enumerate()
+            .map(|(位置, 键)| {
+                let 默认代价 = if 位置 == 0 { 0.0 } else { 1.0 };
+                代价
+                    .as_ref()
+                    .and_then(|x| x.get(键).copied())
+                    .unwrap_or(默认代价)
+            })
+            .collect();
+        let 最后 = *列表.last().unwrap_or(&0.0);
+        Ok((0..=u8::MAX as usize)
+            .map(|位置| *列表.get(位置).unwrap_or(&最后))
+            .collect())
+    }
+
     pub fn 计算复杂度(&self, 决策: &默认决策) -> f64 {
         let mut 复杂度 = 0.0;
         for (序号, 安排列表) in self.决策空间.元素.iter().enumerate() {
@@ -94,6 +622,9 @@ impl<E: 编码器<决策 = 默认决策>> 默认目标函数<E> {
             }
             复杂度 += 分值;
         }
+        for (候选拆分, 序号) in self.决策空间.拆分.iter().zip(&决策.拆分) {
+            复杂度 += 候选拆分.分数[*序号];
+        }
         复杂度
     }
 }
@@ -131,6 +662,9 @@ impl<E: 编码器<决策 = 默认决策>> 目标函数 for 默认目标函数<E>
             characters_short: None,
             words_short: None,
             complexity: None,
+            learnability: None,
+            feasible: None,
+            violation: None,
         };
         for (桶索引, 桶) in self.计数桶列表.iter().enumerate() {
             let _ = &桶[0].as_ref().map(|x| {
@@ -152,9 +686,555 @@ impl<E: 编码器<决策 = 默认决策>> 目标函数 for 默认目标函数<E>
                 }
             });
         }
-        let 复杂度 = self.计算复杂度(决策);
+        let mut 复杂度 = self.计算复杂度(决策);
+        if let Some(亲和) = &mut self.亲和 {
+            复杂度 += 亲和.计算(决策, 变化);
+        }
         指标.complexity = Some(复杂度);
         目标函数 += 参数.正则化强度 * 复杂度;
+        if let Some(易学性) = &self.易学性 {
+            let (易学性指标, 易学性损失) = 易学性.计算(决策);
+            指标.learnability = Some(易学性指标);
+            目标函数 += 易学性损失;
+        }
+        if let Some(表达式) = &self.表达式 {
+            目标函数 = 表达式.求值(&指标, 目标函数);
+        }
+        if let Some(约束) = &mut self.约束 {
+            let 违反量 = 约束.计算违反量(&指标, 目标函数);
+            指标.feasible = Some(违反量 == 0.0);
+            指标.violation = Some(违反量);
+            约束.记录(违反量);
+        }
         (指标, 目标函数)
     }
+
+    fn 可行(指标: &默认指标) -> bool {
+        指标.feasible.unwrap_or(true)
+    }
+
+    fn 差值(&self, 甲: &(默认指标, f64), 乙: &(默认指标, f64)) -> f64 {
+        let Some(约束) = &self.约束 else {
+            return 甲.1 - 乙.1;
+        };
+        let 违反量 = |x: &默认指标| x.violation.unwrap_or(0.0);
+        约束.差值((甲.1, 违反量(&甲.0)), (乙.1, 违反量(&乙.0)))
+    }
+
+    fn 附加说明(&self, 指标: &mut 默认指标) {
+        指标.附加指法名称(&self.参数.自定义指法名称);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::配置;
+    use crate::encoders::default::默认编码器;
+    use crate::interfaces::默认输入;
+    use crate::objectives::metric::{指法标记, 键盘几何};
+    use crate::{
+        原始可编码对象, 原始当量信息, 原始时间模型, 原始键位分布信息, 最大按键组合长度, 棱镜,
+    };
+
+    const 测试时间模型: &str = "base\t100
+key\tf\t80
+same_key\t60\t0\t0
+same_finger\t150\t40\t20
+same_hand\t110\t10\t5
+alternate\t90\t0\t0
+finger\t3\t3\t200\t50\t30
+select\t300
+auto_commit\t20
+";
+
+    #[test]
+    fn test_long_code_sliding_window() {
+        let 按键 = ['q', 'w', 'e', 'r', 'a', 's'];
+        let 进制 = 按键.len() as u64 + 1;
+        let 数字转键: FxHashMap<u64, char> = (1..进制).zip(按键.iter().copied()).collect();
+        let 棱镜 = 棱镜 {
+            键转数字: 数字转键.iter().map(|(k, v)| (*v, *k)).collect(),
+            数字转键: 数字转键.clone(),
+            元素转数字: FxHashMap::default(),
+            数字转元素: FxHashMap::default(),
+            进制,
+        };
+        // 给每个二元、三元和四元组合一个互不相同的当量
+        let mut 原始当量信息 = 原始当量信息::default();
+        let mut 序号 = 1.0;
+        for 编码 in 进制..进制.pow(4) {
+            let 组合: String = 棱镜.数字转编码(编码).iter().collect();
+            if 组合.chars().count() >= 2 {
+                原始当量信息.entry(组合).or_insert_with(|| {
+                    序号 += 1.0;
+                    序号 / 1000.0
+                });
+            }
+        }
+        let 空间 = 进制.pow(最大按键组合长度 as u32) as usize;
+        let 键盘几何 = 键盘几何::default();
+        let 当量信息 = 棱镜.预处理当量信息(&原始当量信息, 空间);
+        let 指法计数 = 棱镜.预处理指法标记(空间, &键盘几何, &[]);
+        let 换手与基准行 = 棱镜.预处理换手与基准行(空间, &键盘几何);
+        let 时间模型 = 原始时间模型::解析(测试时间模型).unwrap();
+        let 时间信息 = 棱镜.预处理时间信息(&时间模型, 空间, &键盘几何);
+        let 参数 = 默认目标函数参数 {
+            键位分布信息: vec![],
+            当量尾部: 计算尾部(&当量信息, 进制, |x, y| x - y),
+            指法尾部: 计算尾部(&指法计数, 进制, |mut x, y| {
+                x.iter_mut().zip(y).for_each(|(a, b)| *a -= b);
+                x
+            }),
+            当量信息,
+            指法计数,
+            进制,
+            数字转键,
+            正则化强度: 1.0,
+            自定义指法名称: vec![],
+            按键手指: vec![],
+            手指分布信息: vec![],
+            左右手分布信息: vec![],
+            选择代价: vec![0.0; u8::MAX as usize + 1],
+            时间尾部: 计算尾部(&时间信息, 进制, |x, y| x - y),
+            时间信息,
+            选重开销: 时间模型.选重开销,
+            自动上屏开销: 时间模型.自动上屏开销,
+            是选择键: vec![false; 进制 as usize],
+            换手与基准行尾部: 计算尾部(&换手与基准行, 进制, |x, y| {
+                [x[0] - y[0], x[1] - y[1]]
+            }),
+            换手与基准行,
+            并击乘数: None,
+        };
+        let 指法标记 = 指法标记::new(&键盘几何);
+        let mut 编码: 编码 = 1;
+        for _ in 0..2000 {
+            编码 = (编码 * 7919 + 104729) % 进制.pow(8);
+            // 只检验超过四个按键且中间没有空位的编码
+            let 按键序列 = 棱镜.数字转编码(编码);
+            let 位数 = (0..).take_while(|i| 进制.pow(*i) <= 编码).count();
+            if 按键序列.len() != 位数 || 位数 <= 最大按键组合长度 {
+                continue;
+            }
+            let mut 当量 = 0.0;
+            for 长度 in 2..=4 {
+                for 组合 in 按键序列.windows(长度) {
+                    当量 += 原始当量信息
+                        .get(&组合.iter().collect::<String>())
+                        .unwrap_or(&0.0);
+                }
+            }
+            assert!((参数.编码当量(编码) - 当量).abs() < 1e-9);
+            assert_eq!(参数.编码指法(编码), 指法标记.计算指法向量(&按键序列, &[]));
+            let 位置: Vec<_> = 按键序列.iter().map(|x| 键盘几何.按键[x]).collect();
+            let 换手 = 位置.windows(2).filter(|x| x[0].左手 != x[1].左手).count();
+            let 基准行 = 位置.iter().filter(|x| x.行 == 键盘几何.基准行).count();
+            assert_eq!(参数.编码换手与基准行(编码), [换手 as i64, 基准行 as i64]);
+            // 长编码的时间等于各个按键的时间加上相邻两个按键之间的转移时间，都可以从短编码的表中查到
+            let 按键: Vec<_> = 按键序列.iter().map(|x| 棱镜.键转数字[x]).collect();
+            let 单键 = |x: u64| 参数.时间信息[x as usize];
+            let mut 时间 = 时间模型.自动上屏开销 + 时间模型.选重开销;
+            时间 += 按键.iter().map(|x| 单键(*x)).sum::<f64>();
+            for 组合 in 按键.windows(2) {
+                时间 += 单键(组合[0] + 组合[1] * 进制) - 单键(组合[0]) - 单键(组合[1]);
+            }
+            let 末键 = *按键.last().unwrap();
+            assert!((参数.编码时间(编码, 末键, 1) - 时间).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_time_model() {
+        let 模型 = 原始时间模型::解析(测试时间模型).unwrap();
+        assert_eq!(模型.基础按键时间, 100.0);
+        assert_eq!(模型.按键时间[&'f'], 80.0);
+        assert_eq!(模型.转移时间[1].每行, 40.0);
+        assert_eq!(模型.手指转移时间[3][3].unwrap().每列, 30.0);
+        assert!(模型.手指转移时间[3][2].is_none());
+        assert_eq!((模型.选重开销, 模型.自动上屏开销), (300.0, 20.0));
+        for 错误行 in [
+            "finger\t3\t10\t1\t0\t0",
+            "same_hand\t1\t2",
+            "fast\t1",
+            "key\tf",
+        ] {
+            assert!(原始时间模型::解析(错误行).is_err(), "{}", 错误行);
+        }
+        let 按键 = ['d', 'f', 'g', 'j', 't'];
+        let 进制 = 按键.len() as u64 + 1;
+        let 数字转键: FxHashMap<u64, char> = (1..进制).zip(按键.iter().copied()).collect();
+        let 棱镜 = 棱镜 {
+            键转数字: 数字转键.iter().map(|(k, v)| (*v, *k)).collect(),
+            数字转键,
+            元素转数字: FxHashMap::default(),
+            数字转元素: FxHashMap::default(),
+            进制,
+        };
+        let 时间信息 =
+            棱镜.预处理时间信息(&模型, (进制 * 进制) as usize, &键盘几何::default());
+        let 时间 = |编码: &str| {
+            let 编码 = 编码
+                .chars()
+                .rev()
+                .fold(0, |x, c| x * 进制 + 棱镜.键转数字[&c]);
+            时间信息[编码 as usize]
+        };
+        // 同键
+        assert_eq!(时间("ff"), 80.0 + 80.0 + 60.0);
+        // 左手食指到左手食指，使用单独给出的手指组合，而不是同指
+        assert_eq!(时间("fg"), 80.0 + 100.0 + 200.0 + 30.0);
+        assert_eq!(时间("gt"), 100.0 + 100.0 + 200.0 + 50.0);
+        // 没有单独给出的手指组合按照同手和异手计算
+        assert_eq!(时间("dg"), 100.0 + 100.0 + 110.0 + 5.0 * 2.0);
+        assert_eq!(时间("fj"), 80.0 + 100.0 + 90.0);
+    }
+
+    const 增量测试方案: &str = r#"
+form:
+  alphabet: qwerasdfhjklnui
+  mapping: {口: q, 日: w, 木: e, 水: r, 火: a, 土: s, 金: d, 人: f}
+  mapping_space:
+    口: [{value: q, score: 0}, {value: h, score: 0}, {value: w, score: 0}]
+    日: [{value: w, score: 0}, {value: n, score: 0}, {value: q, score: 0}]
+    木: [{value: e, score: 0}, {value: j, score: 0}]
+    水: [{value: r, score: 0}, {value: k, score: 0}]
+    火: [{value: a, score: 0}, {value: l, score: 0}]
+    土: [{value: s, score: 0}, {value: u, score: 0}]
+    金: [{value: d, score: 0}, {value: i, score: 0}]
+    人: [{value: f, score: 0}, {value: h, score: 0}, {value: {element: 口}, score: 0}]
+encoder:
+  max_length: 3
+  select_keys: ["_", ";"]
+  short_code:
+    - length_equal: 1
+      schemes:
+        - prefix: 1
+        - prefix: 2
+"#;
+
+    fn 增量测试配置(优化配置: &str) -> 配置 {
+        serde_yaml::from_str(&format!("{增量测试方案}{优化配置}")).unwrap()
+    }
+
+    /// 用固定的伪随机序列生成单字和多字词，逐步变异，检验增量计算的指标与从头计算的相同
+    fn 检查增量计算(配置: 配置, 附加输入: 默认输入) {
+        use crate::operators::default::测试工具::{伪随机, 逐步变异};
+
+        let 元素 = ["口", "日", "木", "水", "火", "土", "金", "人"];
+        let mut 随机数 = 伪随机(12345);
+        let 词列表: Vec<_> = (0..240)
+            .map(|序号: u32| {
+                let 字 = |x: u32| char::from_u32(0x4e00 + x).unwrap();
+                let 词: String = if 序号 < 160 {
+                    字(序号).to_string()
+                } else {
+                    [字(序号 % 160), 字(序号 * 7 % 160)].iter().collect()
+                };
+                原始可编码对象 {
+                    词,
+                    元素序列: 随机数.元素序列(&元素, 2, 3),
+                    频率: (1000 - 序号) as u64,
+                    简码长度: u64::MAX,
+                    固定排名: None,
+                }
+            })
+            .collect();
+        let 上下文 = 默认上下文::新建(默认输入 {
+            配置,
+            词列表,
+            ..附加输入
+        })
+        .unwrap();
+        let 新建目标函数 = || {
+            let 编码器 = 默认编码器::新建(&上下文).unwrap();
+            默认目标函数::新建(&上下文, 编码器).unwrap()
+        };
+        let mut 目标函数 = 新建目标函数();
+        目标函数.计算(&上下文.初始决策, &None);
+        逐步变异(&上下文, 100, |步骤, 尝试决策, 变化| {
+            let (增量指标, 增量损失) = 目标函数.计算(尝试决策, &Some(变化));
+            let (全量指标, 全量损失) = 新建目标函数().计算(尝试决策, &None);
+            let 甲 = serde_json::to_value(&增量指标).unwrap();
+            let 乙 = serde_json::to_value(&全量指标).unwrap();
+            assert!(近似相等(&甲, &乙), "第 {} 步：{} != {}", 步骤, 甲, 乙);
+            assert!((增量损失 - 全量损失).abs() < 1e-6 * 全量损失.abs().max(1.0));
+        });
+    }
+
+    fn 近似相等(甲: &serde_json::Value, 乙: &serde_json::Value) -> bool {
+        use serde_json::Value;
+        match (甲, 乙) {
+            (Value::Number(x), Value::Number(y)) => {
+                let (x, y) = (x.as_f64().unwrap(), y.as_f64().unwrap());
+                (x - y).abs() < 1e-6 * x.abs().max(1.0)
+            }
+            (Value::Array(x), Value::Array(y)) => {
+                x.len() == y.len() && x.iter().zip(y).all(|(x, y)| 近似相等(x, y))
+            }
+            (Value::Object(x), Value::Object(y)) => {
+                x.len() == y.len()
+                    && x.iter()
+                        .all(|(键, x)| y.get(键).is_some_and(|y| 近似相等(x, y)))
+            }
+            _ => 甲 == 乙,
+        }
+    }
+
+    #[test]
+    fn test_incremental_typing_time() {
+        let 优化配置 = r#"
+optimization:
+  objective:
+    characters_full:
+      typing_time: 1
+      tiers: [{top: 40, duplication: 1}]
+    characters_short: {typing_time: 1}
+    words_full: {typing_time: 1}
+"#;
+        let 时间模型 = 原始时间模型::解析(测试时间模型).unwrap();
+        检查增量计算(
+            增量测试配置(优化配置),
+            默认输入 {
+                原始时间模型: Some(时间模型),
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn test_incremental_selection_cost() {
+        let 优化配置 = r#"
+optimization:
+  objective:
+    characters_full: {selection: 1, duplication: 1}
+    characters_short: {selection: 1}
+    words_full: {selection: 1}
+    selection_costs: {"_": 0.5, ";": 2}
+"#;
+        检查增量计算(增量测试配置(优化配置), 默认输入::default());
+    }
+
+    #[test]
+    fn test_incremental_tier_equivalence_and_distribution() {
+        let 优化配置 = r#"
+optimization:
+  objective:
+    characters_full:
+      pair_equivalence: 1
+      extended_pair_equivalence: 1
+      key_distribution: 1
+      key_distribution_loss: squared
+      tiers:
+        - {top: 40, pair_equivalence: 1, key_distribution: 1}
+        - {top: 120, pair_equivalence: 1, key_distribution: 1}
+    characters_short:
+      tiers: [{top: 40, pair_equivalence: 1, key_distribution: 1}]
+    words_full:
+      extended_pair_equivalence: 1
+      tiers: [{top: 30, pair_equivalence: 1, key_distribution: 1}]
+"#;
+        // 给每个二元组合和每个按键一个互不相同的当量和理想值
+        let 按键: Vec<_> = "qwerasdfhjklnui_;".chars().collect();
+        let mut 原始当量信息 = 原始当量信息::default();
+        let mut 原始键位分布信息 = 原始键位分布信息::default();
+        for (序号, 甲) in 按键.iter().enumerate() {
+            for (次序号, 乙) in 按键.iter().enumerate() {
+                let 当量 = 1.0 + ((序号 * 7 + 次序号 * 3) % 11) as f64 / 10.0;
+                原始当量信息.insert(format!("{甲}{乙}"), 当量);
+            }
+            let 损失函数 = 键位分布损失函数 {
+                理想值: (序号 % 5 + 3) as f64,
+                低于惩罚: 1.0,
+                高于惩罚: 0.5,
+            };
+            原始键位分布信息.insert(*甲, 损失函数);
+        }
+        检查增量计算(
+            增量测试配置(优化配置),
+            默认输入 {
+                原始当量信息,
+                原始键位分布信息,
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn test_extended_pair_equivalence() {
+        let 配置 = r#"
+form:
+  alphabet: qwer
+  mapping: {口: q, 日: w, 木: e}
+encoder:
+  max_length: 2
+optimization:
+  objective:
+    characters_full: {extended_pair_equivalence: 1}
+"#;
+        let 词 = |词: &str, 元素序列: &str, 频率| 原始可编码对象 {
+            词: 词.to_string(),
+            元素序列: 元素序列.to_string(),
+            频率,
+            简码长度: u64::MAX,
+            固定排名: None,
+        };
+        let 原始当量信息: 原始当量信息 = [("wq", 1.0), ("we", 2.0), ("qq", 3.0), ("qe", 4.0)]
+            .iter()
+            .map(|(k, v)| (k.to_string(), *v))
+            .collect();
+        let 上下文 = 默认上下文::新建(默认输入 {
+            配置: serde_yaml::from_str(配置).unwrap(),
+            词列表: vec![词("一", "口 日", 3), 词("二", "木 口", 1)],
+            原始当量信息,
+            ..Default::default()
+        })
+        .unwrap();
+        let 编码器 = 默认编码器::新建(&上下文).unwrap();
+        let mut 目标函数 = 默认目标函数::新建(&上下文, 编码器).unwrap();
+        let (指标, _) = 目标函数.计算(&上下文.初始决策, &None);
+        // 末键 w、q 的频率为 3、1，首键 q、e 的频率为 3、1，
+        // 词间当量为 (3×3×wq + 3×1×we + 1×3×qq + 1×1×qe) / (4×4)
+        let 词间当量 = 指标
+            .characters_full
+            .unwrap()
+            .extended_pair_equivalence
+            .unwrap();
+        assert!((词间当量 - 28.0 / 16.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_incremental_coverage_and_charset_tiers() {
+        let 优化配置 = r#"
+optimization:
+  objective:
+    characters_full:
+      tiers:
+        - {coverage: 0.5, duplication: 1, levels: [{length: 2, frequency: 1}]}
+        - {charset: chars.txt, duplication: 1, levels: [{length: 2, frequency: 1}]}
+    characters_short:
+      tiers: [{charset: chars.txt, duplication: 1}]
+    words_full:
+      tiers: [{coverage: 0.9, duplication: 1}, {charset: chars.txt, duplication: 1}]
+"#;
+        // 字符集中的字不连续，这样层级不是一个简单的前缀
+        let 字符集: String = (0..160)
+            .filter(|x| x % 3 != 1)
+            .map(|x| char::from_u32(0x4e00 + x).unwrap())
+            .collect();
+        检查增量计算(
+            增量测试配置(优化配置),
+            默认输入 {
+                字符集: vec![("chars.txt".to_string(), 字符集)]
+                    .into_iter()
+                    .collect(),
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn test_incremental_learnability() {
+        // 人可以归并到口上，所以除了配置中给出的变体，人也自动视为口的变体
+        let 优化配置 = r#"
+optimization:
+  objective:
+    characters_full: {duplication: 1}
+    learnability:
+      classes: {横: [口, 日, 木, 人], 竖: [水, 火, 土, 金]}
+      variants: {日: 口, 土: 水}
+      key_spread: 0.1
+      variant_mismatch: 1
+      key_entropy: 0.5
+"#;
+        检查增量计算(增量测试配置(优化配置), 默认输入::default());
+    }
+
+    #[test]
+    fn test_incremental_affinity() {
+        let mut 配置 = 增量测试配置(
+            r#"
+optimization:
+  objective:
+    characters_full: {duplication: 1}
+    regularization_strength: 0.1
+"#,
+        );
+        // 人可能归并到口上，这时口移动时人也会随之移动，和人有关的亲和项也要重新计算
+        let 亲和 = |甲: &str, 乙: &str, 同键, 分数| 元素亲和 {
+            elements: [甲.to_string(), 乙.to_string()],
+            same_key: Some(同键),
+            score: 分数,
+        };
+        配置.form.mapping_affinities = Some(vec![
+            亲和("口", "日", true, -3.0),
+            亲和("人", "木", true, -2.0),
+            亲和("人", "日", false, 1.5),
+            亲和("水", "火", false, 2.0),
+        ]);
+        检查增量计算(配置, 默认输入::default());
+    }
+
+    #[test]
+    fn test_incremental_alternation_and_finger_load() {
+        let 优化配置 = r#"
+optimization:
+  objective:
+    characters_full:
+      alternation: -1
+      home_row: -1
+      finger_distribution: 1
+      hand_distribution: 1
+      tiers:
+        - {top: 40, alternation: -1, home_row: -1, finger_distribution: 1}
+        - {coverage: 0.8, finger_distribution: 1}
+    characters_short:
+      alternation: -1
+      home_row: -1
+      tiers: [{top: 40, finger_distribution: 1}]
+    words_full:
+      finger_distribution: 1
+      tiers: [{top: 30, alternation: -1, home_row: -1}]
+    finger_targets: [10, 10, 15, 20, 0, 0, 20, 15, 10, 0]
+    hand_targets: [55, 45]
+"#;
+        检查增量计算(增量测试配置(优化配置), 默认输入::default());
+    }
+
+    #[test]
+    fn test_constraint_scoring() {
+        let 状态 = |模式| 约束求解状态 {
+            条件列表: vec![],
+            模式,
+            初始惩罚系数: 1.0,
+            惩罚系数: 1.0,
+            评测次数: 0,
+            可行次数: 0,
+        };
+        // 字典序模式下，可行解总是优于不可行解，违反量相同时比较损失
+        let 字典序 = 状态(约束模式::Lexicographic);
+        assert!(字典序.差值((10.0, 0.0), (1.0, 0.5)) < 0.0);
+        assert!(字典序.差值((1.0, 0.2), (0.5, 0.5)) < 0.0);
+        assert!(字典序.差值((1.0, 0.5), (2.0, 0.5)) < 0.0);
+        assert!(字典序.差值((2.0, 0.0), (1.0, 0.0)) > 0.0);
+        // 违反量不同时差值就是违反量之差，与损失的大小无关，所以退火接受违反量增大的概率只取决于违反量之差和温度
+        assert_eq!(字典序.差值((0.0, 0.5), (100.0, 0.25)), 0.25);
+        assert_eq!(字典序.差值((100.0, 0.25), (0.0, 0.5)), -0.25);
+        assert_eq!(字典序.差值((1e6, 0.5), (0.0, 0.25)), 0.25);
+        // 惩罚模式下，损失较低的不可行解可能优于可行解，直到惩罚系数变大
+        let mut 惩罚 = 状态(约束模式::Penalty);
+        let (可行解, 不可行解) = ((2.0, 0.0), (1.0, 0.5));
+        assert!(惩罚.差值(不可行解, 可行解) < 0.0);
+        for _ in 0..惩罚调整间隔 * 2 {
+            惩罚.记录(0.5);
+        }
+        assert_eq!(惩罚.惩罚系数, 4.0);
+        assert!(惩罚.差值(不可行解, 可行解) > 0.0);
+        assert_eq!(惩罚.差值(不可行解, 可行解), -惩罚.差值(可行解, 不可行解));
+        // 全都可行时惩罚系数减小，但不低于初始值
+        for _ in 0..惩罚调整间隔 * 3 {
+            惩罚.记录(0.0);
+        }
+        assert_eq!(惩罚.惩罚系数, 1.0);
+    }
 }
@@ -0,0 +1,716 @@
+//! 目标函数表达式
+//!
+//! 用户可以在 `objective.expression` 中用一个简单的算术表达式来定义标量化的目标函数，表达式中可以引用默认指标中的各项指标。支持的语法如下：
+//!
+//! - 数字、括号、四则运算 `+ - * /`、乘方 `^` 和比较运算 `< <= > >=`（成立时为 1，否则为 0）；
+//! - 函数 `max`、`min`（任意多个参数）、`log`、`exp`、`abs`、`sqrt` 和 `if(条件, 成立时的值, 不成立时的值)`；
+//! - 变量 `loss` 为按权重加权得到的原目标函数值（包括正则化项），`complexity` 为复杂度，`learnability.key_spread`、`learnability.variant_mismatch` 和 `learnability.key_entropy` 为易学性指标；
+//! - 形如 `characters_full.duplication` 的变量为各个分组中的指标，分组为 `characters_full`、`characters_short`、`words_full`、`words_short` 之一，指标可以是 `duplication`、`key_distribution_loss`、`finger_distribution_loss`、`hand_distribution_loss`、`pair_equivalence`、`extended_pair_equivalence`、`selection`、`typing_time`、`alternation`、`home_row`、`fingering.序号`（从 0 开始）和 `levels.码长`；
+//! - 形如 `characters_full.tiers.0.duplication` 的变量为分级指标，可以是 `duplication`、`pair_equivalence`、`key_distribution_loss`、`finger_distribution_loss`、`alternation`、`home_row`、`fingering.序号` 和 `levels.码长`。
+//!
+//! 例如，「在一字全码选重率不超过 0.5% 的前提下最小化组合当量」可以写成 `characters_full.pair_equivalence + 1000 * max(0, characters_full.duplication - 0.005)`。
+
+use super::metric::{分组指标, 层级指标, 默认指标};
+use crate::config::{层级权重, 易学性配置, 目标配置, 码长权重, 部分权重};
+use crate::错误;
+
+#[derive(Debug, Clone, Copy)]
+enum 分组 {
+    一字全码,
+    一字简码,
+    多字全码,
+    多字简码,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum 层级字段 {
+    选重,
+    组合当量,
+    键位分布偏差,
+    手指分布偏差,
+    换手率,
+    基准行使用率,
+    指法(usize),
+    码长(usize),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum 分组字段 {
+    选重,
+    键位分布偏差,
+    手指分布偏差,
+    左右手分布偏差,
+    组合当量,
+    词间当量,
+    选择代价,
+    打字时间,
+    换手率,
+    基准行使用率,
+    指法(usize),
+    码长(usize),
+    层级(usize, 层级字段),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum 易学性字段 {
+    键数,
+    变体不同键率,
+    分布熵,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum 变量 {
+    损失,
+    复杂度,
+    易学性(易学性字段),
+    分组(分组, 分组字段),
+}
+
+#[derive(Debug, Clone)]
+enum 节点 {
+    常数(f64),
+    变量(变量, String),
+    取负(Box<节点>),
+    运算(char, Box<节点>, Box<节点>),
+    函数(String, Vec<节点>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum 词法单元 {
+    数字(f64),
+    名称(String),
+    符号(String),
+}
+
+/// 解析后的目标函数表达式
+#[derive(Debug, Clone)]
+pub struct 目标函数表达式 {
+    根: 节点,
+}
+
+fn 词法分析(源: &str) -> Result<Vec<词法单元>, 错误> {
+    let 字符: Vec<char> = 源.chars().collect();
+    let mut 结果 = vec![];
+    let mut i = 0;
+    while i < 字符.len() {
+        let c = 字符[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit()
+            || (c == '.' && 字符.get(i + 1).is_some_and(|x| x.is_ascii_digit()))
+        {
+            let 开始 = i;
+            while i < 字符.len() && (字符[i].is_ascii_digit() || 字符[i] == '.') {
+                i += 1;
+            }
+            // 科学计数法
+            if i < 字符.len() && (字符[i] == 'e' || 字符[i] == 'E') {
+                let mut j = i + 1;
+                if j < 字符.len() && (字符[j] == '+' || 字符[j] == '-') {
+                    j += 1;
+                }
+                if j < 字符.len() && 字符[j].is_ascii_digit() {
+                    i = j;
+                    while i < 字符.len() && 字符[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let 文本: String = 字符[开始..i].iter().collect();
+            let 数值 = 文本
+                .parse()
+                .map_err(|_| format!("表达式中的数字「{文本}」无法解析"))?;
+            结果.push(词法单元::数字(数值));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let 开始 = i;
+            while i < 字符.len()
+                && (字符[i].is_ascii_alphanumeric() || 字符[i] == '_' || 字符[i] == '.')
+            {
+                i += 1;
+            }
+            结果.push(词法单元::名称(字符[开始..i].iter().collect()));
+        } else if (c == '<' || c == '>') && 字符.get(i + 1) == Some(&'=') {
+            结果.push(词法单元::符号(format!("{c}=")));
+            i += 2;
+        } else if "+-*/^<>(),".contains(c) {
+            结果.push(词法单元::符号(c.to_string()));
+            i += 1;
+        } else {
+            return Err(format!("表达式中有无法识别的字符「{c}」").into());
+        }
+    }
+    Ok(结果)
+}
+
+struct 解析器 {
+    单元: Vec<词法单元>,
+    位置: usize,
+}
+
+impl 解析器 {
+    fn 查看(&self) -> Option<&词法单元> {
+        self.单元.get(self.位置)
+    }
+
+    fn 是符号(&self, 符号: &str) -> bool {
+        matches!(self.查看(), Some(词法单元::符号(x)) if x == 符号)
+    }
+
+    fn 期望(&mut self, 符号: &str) -> Result<(), 错误> {
+        if !self.是符号(符号) {
+            return Err(format!("表达式中缺少「{符号}」").into());
+        }
+        self.位置 += 1;
+        Ok(())
+    }
+
+    // 比较 < 加减 < 乘除 < 取负 < 乘方
+    fn 比较(&mut self) -> Result<节点, 错误> {
+        let mut 左 = self.加减()?;
+        for (符号, 运算) in [("<=", '≤'), (">=", '≥'), ("<", '<'), (">", '>')] {
+            if self.是符号(符号) {
+                self.位置 += 1;
+                let 右 = self.加减()?;
+                左 = 节点::运算(运算, Box::new(左), Box::new(右));
+                break;
+            }
+        }
+        Ok(左)
+    }
+
+    fn 加减(&mut self) -> Result<节点, 错误> {
+        let mut 左 = self.乘除()?;
+        while self.是符号("+") || self.是符号("-") {
+            let 运算 = if self.是符号("+") { '+' } else { '-' };
+            self.位置 += 1;
+            let 右 = self.乘除()?;
+            左 = 节点::运算(运算, Box::new(左), Box::new(右));
+        }
+        Ok(左)
+    }
+
+    fn 乘除(&mut self) -> Result<节点, 错误> {
+        let mut 左 = self.一元()?;
+        while self.是符号("*") || self.是符号("/") {
+            let 运算 = if self.是符号("*") { '*' } else { '/' };
+            self.位置 += 1;
+            let 右 = self.一元()?;
+            左 = 节点::运算(运算, Box::new(左), Box::new(右));
+        }
+        Ok(左)
+    }
+
+    fn 一元(&mut self) -> Result<节点, 错误> {
+        if self.是符号("-") {
+            self.位置 += 1;
+            return Ok(节点::取负(Box::new(self.一元()?)));
+        }
+        self.乘方()
+    }
+
+    fn 乘方(&mut self) -> Result<节点, 错误> {
+        let 底数 = self.基本()?;
+        if self.是符号("^") {
+            self.位置 += 1;
+            // 乘方是右结合的
+            let 指数 = self.一元()?;
+            return Ok(节点::运算('^', Box::new(底数), Box::new(指数)));
+        }
+        Ok(底数)
+    }
+
+    fn 基本(&mut self) -> Result<节点, 错误> {
+        let 单元 = self.查看().cloned().ok_or("表达式不完整")?;
+        self.位置 += 1;
+        match 单元 {
+            词法单元::数字(x) => Ok(节点::常数(x)),
+            词法单元::符号(x) if x == "(" => {
+                let 内部 = self.比较()?;
+                self.期望(")")?;
+                Ok(内部)
+            }
+            词法单元::名称(名称) if self.是符号("(") => {
+                self.位置 += 1;
+                let mut 参数 = vec![];
+                if !self.是符号(")") {
+                    参数.push(self.比较()?);
+                    while self.是符号(",") {
+                        self.位置 += 1;
+                        参数.push(self.比较()?);
+                    }
+                }
+                self.期望(")")?;
+                let 参数个数正确 = match 名称.as_str() {
+                    "max" | "min" => !参数.is_empty(),
+                    "log" | "exp" | "abs" | "sqrt" => 参数.len() == 1,
+                    "if" => 参数.len() == 3,
+                    _ => return Err(format!("表达式中的函数「{名称}」不存在").into()),
+                };
+                if !参数个数正确 {
+                    return Err(format!("表达式中函数「{名称}」的参数个数不正确").into());
+                }
+                Ok(节点::函数(名称, 参数))
+            }
+            词法单元::名称(名称) => Ok(节点::变量(解析变量(&名称)?, 名称)),
+            词法单元::符号(x) => Err(format!("表达式中出现了意外的「{x}」").into()),
+        }
+    }
+}
+
+fn 解析变量(名称: &str) -> Result<变量, 错误> {
+    let 错误 = || -> 错误 { format!("表达式中的变量「{名称}」不存在").into() };
+    let 片段: Vec<&str> = 名称.split('.').collect();
+    let 序号 = |x: Option<&&str>| x.and_then(|x| x.parse::<usize>().ok()).ok_or_else(错误);
+    match 片段[0] {
+        "loss" if 片段.len() == 1 => return Ok(变量::损失),
+        "complexity" if 片段.len() == 1 => return Ok(变量::复杂度),
+        "learnability" if 片段.len() == 2 => {
+            let 字段 = match 片段[1] {
+                "key_spread" => 易学性字段::键数,
+                "variant_mismatch" => 易学性字段::变体不同键率,
+                "key_entropy" => 易学性字段::分布熵,
+                _ => return Err(错误()),
+            };
+            return Ok(变量::易学性(字段));
+        }
+        _ => {}
+    }
+    let 分组 = match 片段[0] {
+        "characters_full" => 分组::一字全码,
+        "characters_short" => 分组::一字简码,
+        "words_full" => 分组::多字全码,
+        "words_short" => 分组::多字简码,
+        _ => return Err(错误()),
+    };
+    let 字段 = match (片段.get(1).copied(), 片段.len()) {
+        (Some("duplication"), 2) => 分组字段::选重,
+        (Some("key_distribution_loss"), 2) => 分组字段::键位分布偏差,
+        (Some("finger_distribution_loss"), 2) => 分组字段::手指分布偏差,
+        (Some("hand_distribution_loss"), 2) => 分组字段::左右手分布偏差,
+        (Some("pair_equivalence"), 2) => 分组字段::组合当量,
+        (Some("extended_pair_equivalence"), 2) => 分组字段::词间当量,
+        (Some("selection"), 2) => 分组字段::选择代价,
+        (Some("typing_time"), 2) => 分组字段::打字时间,
+        (Some("alternation"), 2) => 分组字段::换手率,
+        (Some("home_row"), 2) => 分组字段::基准行使用率,
+        (Some("fingering"), 3) => 分组字段::指法(序号(片段.get(2))?),
+        (Some("levels"), 3) => 分组字段::码长(序号(片段.get(2))?),
+        (Some("tiers"), 4 | 5) => {
+            let 层级字段 = match (片段[3], 片段.len()) {
+                ("duplication", 4) => 层级字段::选重,
+                ("pair_equivalence", 4) => 层级字段::组合当量,
+                ("key_distribution_loss", 4) => 层级字段::键位分布偏差,
+                ("finger_distribution_loss", 4) => 层级字段::手指分布偏差,
+                ("alternation", 4) => 层级字段::换手率,
+                ("home_row", 4) => 层级字段::基准行使用率,
+                ("fingering", 5) => 层级字段::指法(序号(片段.get(4))?),
+                ("levels", 5) => 层级字段::码长(序号(片段.get(4))?),
+                _ => return Err(错误()),
+            };
+            分组字段::层级(序号(片段.get(2))?, 层级字段)
+        }
+        _ => return Err(错误()),
+    };
+    if let 分组字段::指法(i) | 分组字段::层级(_, 层级字段::指法(i)) = 字段 {
+        if i >= 8 {
+            return Err(错误());
+        }
+    }
+    Ok(变量::分组(分组, 字段))
+}
+
+impl 目标函数表达式 {
+    pub fn 新建(源: &str, 配置: &目标配置) -> Result<Self, 错误> {
+        let 表达式 = Self::解析(源)?;
+        表达式.检查(&表达式.根, 配置)?;
+        Ok(表达式)
+    }
+
+    /// 只解析表达式，不检查引用的指标是否配置了权重
+    pub fn 解析(源: &str) -> Result<Self, 错误> {
+        let mut 解析器 = 解析器 {
+            单元: 词法分析(源)?,
+            位置: 0,
+        };
+        let 根 = 解析器.比较()?;
+        if 解析器.位置 != 解析器.单元.len() {
+            return Err("表达式末尾有多余的内容".into());
+        }
+        Ok(Self { 根 })
+    }
+
+    /// 约束中引用的指标不一定配置了权重，为没有权重的指标补上零权重，使它们也会被计算，但不影响目标函数
+    pub fn 补充权重(&self, 配置: &mut 目标配置) -> Result<(), 错误> {
+        Self::补充节点(&self.根, 配置)
+    }
+
+    fn 补充节点(节点: &节点, 配置: &mut 目标配置) -> Result<(), 错误> {
+        match 节点 {
+            节点::常数(_) => Ok(()),
+            节点::变量(变量, 名称) => Self::补充变量(变量, 名称, 配置),
+            节点::取负(x) => Self::补充节点(x, 配置),
+            节点::运算(_, x, y) => {
+                Self::补充节点(x, 配置)?;
+                Self::补充节点(y, 配置)
+            }
+            节点::函数(_, 参数) => 参数.iter().try_for_each(|x| Self::补充节点(x, 配置)),
+        }
+    }
+
+    fn 补充变量(
+        变量: &变量, 名称: &str, 配置: &mut 目标配置
+    ) -> Result<(), 错误> {
+        let (分组, 字段) = match 变量 {
+            变量::损失 | 变量::复杂度 => return Ok(()),
+            变量::易学性(字段) => {
+                let 易学性 = 配置.learnability.get_or_insert_with(易学性配置::default);
+                let 权重 = match 字段 {
+                    易学性字段::键数 => &mut 易学性.key_spread,
+                    易学性字段::变体不同键率 => &mut 易学性.variant_mismatch,
+                    易学性字段::分布熵 => &mut 易学性.key_entropy,
+                };
+                权重.get_or_insert(0.0);
+                return Ok(());
+            }
+            变量::分组(分组, 字段) => (分组, 字段),
+        };
+        let 权重 = match 分组 {
+            分组::一字全码 => &mut 配置.characters_full,
+            分组::一字简码 => &mut 配置.characters_short,
+            分组::多字全码 => &mut 配置.words_full,
+            分组::多字简码 => &mut 配置.words_short,
+        }
+        .get_or_insert_with(部分权重::default);
+        let 补充码长 = |levels: &mut Option<Vec<码长权重>>, length: usize| {
+            let levels = levels.get_or_insert_with(Vec::new);
+            if !levels.iter().any(|x| x.length == length) {
+                levels.push(码长权重 {
+                    length,
+                    frequency: 0.0,
+                });
+            }
+        };
+        let 字段权重 = match 字段 {
+            分组字段::选重 => &mut 权重.duplication,
+            分组字段::键位分布偏差 => &mut 权重.key_distribution,
+            分组字段::手指分布偏差 => &mut 权重.finger_distribution,
+            分组字段::左右手分布偏差 => &mut 权重.hand_distribution,
+            分组字段::组合当量 => &mut 权重.pair_equivalence,
+            分组字段::词间当量 => &mut 权重.extended_pair_equivalence,
+            分组字段::选择代价 => &mut 权重.selection,
+            分组字段::打字时间 => &mut 权重.typing_time,
+            分组字段::换手率 => &mut 权重.alternation,
+            分组字段::基准行使用率 => &mut 权重.home_row,
+            分组字段::指法(i) => &mut 权重.fingering.get_or_insert([None; 8])[*i],
+            分组字段::码长(长度) => {
+                补充码长(&mut 权重.levels, *长度);
+                return Ok(());
+            }
+            分组字段::层级(序号, 层级字段) => {
+                // 层级的范围无法凭空补上，所以引用的层级必须已经配置
+                let Some(层级) = 权重.tiers.as_mut().and_then(|x| x.get_mut(*序号)) else {
+                    return Err(format!("指标「{名称}」引用的层级没有在目标函数中配置").into());
+                };
+                match 层级字段 {
+                    层级字段::选重 => &mut 层级.duplication,
+                    层级字段::组合当量 => &mut 层级.pair_equivalence,
+                    层级字段::键位分布偏差 => &mut 层级.key_distribution,
+                    层级字段::手指分布偏差 => &mut 层级.finger_distribution,
+                    层级字段::换手率 => &mut 层级.alternation,
+                    层级字段::基准行使用率 => &mut 层级.home_row,
+                    层级字段::指法(i) => &mut 层级.fingering.get_or_insert([None; 8])[*i],
+                    层级字段::码长(长度) => {
+                        补充码长(&mut 层级.levels, *长度);
+                        return Ok(());
+                    }
+                }
+            }
+        };
+        字段权重.get_or_insert(0.0);
+        Ok(())
+    }
+
+    /// 只有配置了权重的指标才会被计算，所以表达式中引用的指标都必须在配置中有对应的权重
+    fn 检查(&self, 节点: &节点, 配置: &目标配置) -> Result<(), 错误> {
+        match 节点 {
+            节点::常数(_) => Ok(()),
+            节点::变量(变量, 名称) => {
+                if Self::已配置(变量, 配置) {
+                    Ok(())
+                } else {
+                    Err(format!("表达式中引用的指标「{名称}」没有在目标函数中配置权重").into())
+                }
+            }
+            节点::取负(x) => self.检查(x, 配置),
+            节点::运算(_, x, y) => {
+                self.检查(x, 配置)?;
+                self.检查(y, 配置)
+            }
+            节点::函数(_, 参数) => 参数.iter().try_for_each(|x| self.检查(x, 配置)),
+        }
+    }
+
+    fn 已配置(变量: &变量, 配置: &目标配置) -> bool {
+        let (分组, 字段) = match 变量 {
+            变量::损失 | 变量::复杂度 => return true,
+            变量::易学性(字段) => {
+                let Some(易学性) = &配置.learnability else {
+                    return false;
+                };
+                return match 字段 {
+                    易学性字段::键数 => 易学性.key_spread.is_some(),
+                    易学性字段::变体不同键率 => 易学性.variant_mismatch.is_some(),
+                    易学性字段::分布熵 => 易学性.key_entropy.is_some(),
+                };
+            }
+            变量::分组(分组, 字段) => (分组, 字段),
+        };
+        let 权重 = match 分组 {
+            分组::一字全码 => &配置.characters_full,
+            分组::一字简码 => &配置.characters_short,
+            分组::多字全码 => &配置.words_full,
+            分组::多字简码 => &配置.words_short,
+        };
+        let Some(权重) = 权重 else {
+            return false;
+        };
+        let 部分权重 {
+            duplication,
+            key_distribution,
+            finger_distribution,
+            hand_distribution,
+            pair_equivalence,
+            extended_pair_equivalence,
+            fingering,
+            levels,
+            tiers,
+            selection,
+            typing_time,
+            alternation,
+            home_row,
+            ..
+        } = 权重;
+        match 字段 {
+            分组字段::选重 => duplication.is_some(),
+            分组字段::键位分布偏差 => key_distribution.is_some(),
+            分组字段::手指分布偏差 => finger_distribution.is_some(),
+            分组字段::左右手分布偏差 => hand_distribution.is_some(),
+            分组字段::组合当量 => pair_equivalence.is_some(),
+            分组字段::词间当量 => extended_pair_equivalence.is_some(),
+            分组字段::选择代价 => selection.is_some(),
+            分组字段::打字时间 => typing_time.is_some(),
+            分组字段::换手率 => alternation.is_some(),
+            分组字段::基准行使用率 => home_row.is_some(),
+            分组字段::指法(i) => fingering.is_some_and(|x| x[*i].is_some()),
+            分组字段::码长(长度) => levels.iter().flatten().any(|x| x.length == *长度),
+            分组字段::层级(序号, 层级字段) => {
+                let Some(层级权重 {
+                    duplication,
+                    fingering,
+                    levels,
+                    pair_equivalence,
+                    key_distribution,
+                    finger_distribution,
+                    alternation,
+                    home_row,
+                    ..
+                }) = tiers.as_ref().and_then(|x| x.get(*序号))
+                else {
+                    return false;
+                };
+                match 层级字段 {
+                    层级字段::选重 => duplication.is_some(),
+                    层级字段::组合当量 => pair_equivalence.is_some(),
+                    层级字段::键位分布偏差 => key_distribution.is_some(),
+                    层级字段::手指分布偏差 => finger_distribution.is_some(),
+                    层级字段::换手率 => alternation.is_some(),
+                    层级字段::基准行使用率 => home_row.is_some(),
+                    层级字段::指法(i) => fingering.is_some_and(|x| x[*i].is_some()),
+                    层级字段::码长(长度) => {
+                        levels.iter().flatten().any(|x| x.length == *长度)
+                    }
+                }
+            }
+        }
+    }
+
+    fn 查找(变量: &变量, 指标: &默认指标, 损失: f64) -> Option<f64> {
+        let (分组, 字段) = match 变量 {
+            变量::损失 => return Some(损失),
+            变量::复杂度 => return 指标.complexity,
+            变量::易学性(字段) => {
+                let 易学性 = 指标.learnability.as_ref()?;
+                return match 字段 {
+                    易学性字段::键数 => 易学性.总键数(),
+                    易学性字段::变体不同键率 => 易学性.variant_mismatch,
+                    易学性字段::分布熵 => 易学性.key_entropy,
+                };
+            }
+            变量::分组(分组, 字段) => (分组, 字段),
+        };
+        let 分组指标: &分组指标 = match 分组 {
+            分组::一字全码 => &指标.characters_full,
+            分组::一字简码 => &指标.characters_short,
+            分组::多字全码 => &指标.words_full,
+            分组::多字简码 => &指标.words_short,
+        }
+        .as_ref()?;
+        match 字段 {
+            分组字段::选重 => 分组指标.duplication,
+            分组字段::键位分布偏差 => 分组指标.key_distribution_loss,
+            分组字段::手指分布偏差 => 分组指标.finger_distribution_loss,
+            分组字段::左右手分布偏差 => 分组指标.hand_distribution_loss,
+            分组字段::组合当量 => 分组指标.pair_equivalence,
+            分组字段::词间当量 => 分组指标.extended_pair_equivalence,
+            分组字段::选择代价 => 分组指标.selection,
+            分组字段::打字时间 => 分组指标.typing_time,
+            分组字段::换手率 => 分组指标.alternation,
+            分组字段::基准行使用率 => 分组指标.home_row,
+            分组字段::指法(i) => 分组指标.fingering.as_ref()?[*i],
+            分组字段::码长(长度) => 分组指标
+                .levels
+                .as_ref()?
+                .iter()
+                .find(|x| x.length == *长度)
+                .map(|x| x.frequency),
+            分组字段::层级(序号, 层级字段) => {
+                let 层级指标: &层级指标 = 分组指标.tiers.as_ref()?.get(*序号)?;
+                let 值 = match 层级字段 {
+                    层级字段::组合当量 => return 层级指标.pair_equivalence,
+                    层级字段::键位分布偏差 => return 层级指标.key_distribution_loss,
+                    层级字段::手指分布偏差 => return 层级指标.finger_distribution_loss,
+                    层级字段::换手率 => return 层级指标.alternation,
+                    层级字段::基准行使用率 => return 层级指标.home_row,
+                    层级字段::选重 => 层级指标.duplication,
+                    层级字段::指法(i) => 层级指标.fingering.as_ref()?[*i],
+                    层级字段::码长(长度) => 层级指标
+                        .levels
+                        .as_ref()?
+                        .iter()
+                        .find(|x| x.length == *长度)
+                        .map(|x| x.frequency),
+                };
+                值.map(|x| x as f64)
+            }
+        }
+    }
+
+    fn 求值节点(节点: &节点, 指标: &默认指标, 损失: f64) -> f64 {
+        let 求值 = |x: &节点| Self::求值节点(x, 指标, 损失);
+        match 节点 {
+            节点::常数(x) => *x,
+            节点::变量(变量, _) => Self::查找(变量, 指标, 损失).unwrap_or(f64::NAN),
+            节点::取负(x) => -求值(x),
+            节点::运算(运算, x, y) => {
+                let (x, y) = (求值(x), 求值(y));
+                let 布尔 = |x: bool| if x { 1.0 } else { 0.0 };
+                match 运算 {
+                    '+' => x + y,
+                    '-' => x - y,
+                    '*' => x * y,
+                    '/' => x / y,
+                    '^' => x.powf(y),
+                    '<' => 布尔(x < y),
+                    '>' => 布尔(x > y),
+                    '≤' => 布尔(x <= y),
+                    _ => 布尔(x >= y),
+                }
+            }
+            节点::函数(名称, 参数) => {
+                let 值: Vec<f64> = 参数.iter().map(求值).collect();
+                match 名称.as_str() {
+                    "max" => 值.into_iter().fold(f64::NEG_INFINITY, f64::max),
+                    "min" => 值.into_iter().fold(f64::INFINITY, f64::min),
+                    "log" => 值[0].ln(),
+                    "exp" => 值[0].exp(),
+                    "abs" => 值[0].abs(),
+                    "sqrt" => 值[0].sqrt(),
+                    _ => {
+                        if 值[0] != 0.0 {
+                            值[1]
+                        } else {
+                            值[2]
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// 用当前的指标和按权重计算得到的损失函数来计算表达式的值
+    pub fn 求值(&self, 指标: &默认指标, 损失: f64) -> f64 {
+        Self::求值节点(&self.根, 指标, 损失)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expression() {
+        let 配置: 目标配置 = serde_yaml::from_str(
+            "characters_full: { duplication: 1, pair_equivalence: 1, levels: [{ length: 2, frequency: 1 }] }",
+        )
+        .unwrap();
+        let 指标: 默认指标 = serde_json::from_str(
+            r#"{ "characters_full": { "duplication": 0.01, "pair_equivalence": 1.5, "levels": [{ "length": 2, "frequency": 0.25 }] }, "complexity": 3 }"#,
+        )
+        .unwrap();
+        let 求值 = |源: &str| 目标函数表达式::新建(源, &配置).unwrap().求值(&指标, 7.0);
+        assert_eq!(求值("1 + 2 * 3 - -2 ^ 2"), 11.0);
+        assert_eq!(求值("2 ^ 3 ^ 2"), 512.0);
+        assert_eq!(求值("loss / 7 + complexity"), 4.0);
+        assert_eq!(求值("characters_full.levels.2 * 4"), 1.0);
+        assert_eq!(
+            求值("characters_full.pair_equivalence + 100 * max(0, characters_full.duplication - 0.005)"),
+            2.0
+        );
+        assert_eq!(
+            求值("if(characters_full.duplication <= 0.01, 1, 2) + (3 > 4)"),
+            1.0
+        );
+        assert_eq!(求值("min(3, sqrt(4), abs(-5)) + log(exp(0))"), 2.0);
+        for 错误表达式 in [
+            "1 +",
+            "(1",
+            "foo(1)",
+            "max()",
+            "words_full.duplication",
+            "characters_full.fingering.0",
+            "1 $ 2",
+        ] {
+            assert!(
+                目标函数表达式::新建(错误表达式, &配置).is_err(),
+                "{}",
+                错误表达式
+            );
+        }
+    }
+
+    #[test]
+    fn test_fill_constraint_weights() {
+        let mut 配置: 目标配置 =
+            serde_yaml::from_str("characters_full: { pair_equivalence: 1, tiers: [{ top: 10 }] }")
+                .unwrap();
+        let 源 = "characters_full.duplication + words_full.fingering.2 + characters_full.tiers.0.levels.3";
+        assert!(目标函数表达式::新建(源, &配置).is_err());
+        目标函数表达式::解析(源)
+            .unwrap()
+            .补充权重(&mut 配置)
+            .unwrap();
+        assert!(目标函数表达式::新建(源, &配置).is_ok());
+        let 一字全码 = 配置.characters_full.as_ref().unwrap();
+        assert_eq!(一字全码.duplication, Some(0.0));
+        assert_eq!(一字全码.pair_equivalence, Some(1.0));
+        let 层级码长 = 一字全码.tiers.as_ref().unwrap()[0].levels.as_ref().unwrap();
+        assert_eq!((层级码长[0].length, 层级码长[0].frequency), (3, 0.0));
+        assert_eq!(
+            配置.words_full.as_ref().unwrap().fingering.unwrap()[2],
+            Some(0.0)
+        );
+        // 层级的范围无法补上
+        let 表达式 = 目标函数表达式::解析("words_short.tiers.0.duplication").unwrap();
+        assert!(表达式.补充权重(&mut 配置).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+//! 易学性指标
+//!
+//! 复杂度只是把每个元素安排的分数相加，并不能反映整个映射是否好记。这里从三个方面衡量映射的规律性：同一类元素分布在多少个键上，变体元素是否和基础元素在同一个键上，以及元素在各个键上分布的熵。
+
+use crate::config::易学性配置;
+use crate::contexts::default::{默认上下文, 默认决策, 默认安排};
+use crate::encoders::default::线性化;
+use crate::{元素, 棱镜, 错误};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct 易学性指标 {
+    pub key_spread: Option<IndexMap<String, usize>>,
+    pub variant_mismatch: Option<f64>,
+    pub key_entropy: Option<f64>,
+}
+
+impl 易学性指标 {
+    /// 各类元素占用的键数之和
+    pub fn 总键数(&self) -> Option<f64> {
+        self.key_spread
+            .as_ref()
+            .map(|x| x.values().sum::<usize>() as f64)
+    }
+}
+
+impl Display for 易学性指标 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(key_spread) = &self.key_spread {
+            for (类别, 键数) in key_spread {
+                f.write_str(&format!("{类别}占用键数：{键数}；"))?;
+            }
+        }
+        if let Some(mismatch) = self.variant_mismatch {
+            f.write_str(&format!("变体不同键率：{:.2}%；", mismatch * 100.0))?;
+        }
+        if let Some(entropy) = self.key_entropy {
+            f.write_str(&format!("元素分布熵：{entropy:.4}；"))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct 易学性评估器 {
+    配置: 易学性配置,
+    棱镜: 棱镜,
+    类别列表: Vec<(String, Vec<元素>)>,
+    变体列表: Vec<(元素, 元素)>,
+}
+
+impl 易学性评估器 {
+    /// 没有给出分类时，所有元素视为一类。除了配置中给出的变体以外，决策空间中可以归并到某个元素上、或者第一码引用了某个元素的元素，也视为那个元素的变体
+    pub fn 新建(上下文: &默认上下文, 配置: &易学性配置) -> Result<Self, 错误> {
+        let 棱镜 = &上下文.棱镜;
+        let 查找 = |名称: &String| {
+            棱镜
+                .元素转数字
+                .get(名称)
+                .copied()
+                .ok_or(format!("易学性配置中的元素 {名称} 不存在"))
+        };
+        let 类别列表 = match &配置.classes {
+            Some(classes) => {
+                let mut 类别列表 = vec![];
+                for (类别, 名称列表) in classes {
+                    let 元素列表 = 名称列表.iter().map(查找).collect::<Result<_, _>>()?;
+                    类别列表.push((类别.clone(), 元素列表));
+                }
+                类别列表
+            }
+            None => {
+                let mut 元素列表: Vec<_> = 棱镜
+                    .数字转元素
+                    .keys()
+                    .copied()
+                    .filter(|x| *x >= 棱镜.进制 as usize)
+                    .collect();
+                元素列表.sort();
+                vec![("元素".to_string(), 元素列表)]
+            }
+        };
+        let mut 变体列表 = vec![];
+        for (变体, 基础) in 配置.variants.iter().flatten() {
+            变体列表.push((查找(变体)?, 查找(基础)?));
+        }
+        for (序号, 条件安排列表) in 上下文.决策空间.元素.iter().enumerate() {
+            let 候选安排 = 条件安排列表
+                .iter()
+                .map(|x| &x.安排)
+                .chain([&上下文.初始决策.元素[序号]]);
+            for 安排 in 候选安排 {
+                let 基础 = match 安排 {
+                    默认安排::归并(基础) => *基础,
+                    默认安排::键位(列表) if 列表[0].0 >= 棱镜.进制 as usize => {
+                        列表[0].0
+                    }
+                    _ => continue,
+                };
+                if !变体列表.contains(&(序号, 基础)) {
+                    变体列表.push((序号, 基础));
+                }
+            }
+        }
+        Ok(Self {
+            配置: 配置.clone(),
+            棱镜: 棱镜.clone(),
+            类别列表,
+            变体列表,
+        })
+    }
+
+    /// 只统计被选取的元素，元素所在的键为它的第一码
+    pub fn 计算(&self, 决策: &默认决策) -> (易学性指标, f64) {
+        let 映射 = 线性化(决策, &self.棱镜);
+        let 选取 = |元素: 元素| !matches!(决策.元素[元素], 默认安排::未选取);
+        let 进制 = self.棱镜.进制 as usize;
+        let mut 指标 = 易学性指标 {
+            key_spread: None,
+            variant_mismatch: None,
+            key_entropy: None,
+        };
+        let mut 损失函数 = 0.0;
+        if let Some(权重) = self.配置.key_spread {
+            let mut key_spread = IndexMap::new();
+            for (类别, 元素列表) in &self.类别列表 {
+                let mut 占用 = vec![false; 进制];
+                for 元素 in 元素列表.iter().filter(|x| 选取(**x)) {
+                    占用[映射[*元素][0] as usize] = true;
+                }
+                let 键数 = 占用[1..].iter().filter(|x| **x).count();
+                损失函数 += 键数 as f64 * 权重;
+                key_spread.insert(类别.clone(), 键数);
+            }
+            指标.key_spread = Some(key_spread);
+        }
+        if let Some(权重) = self.配置.variant_mismatch {
+            let mut 总数 = 0;
+            let mut 不同键数 = 0;
+            for (变体, 基础) in &self.变体列表 {
+                if !选取(*变体) || !选取(*基础) {
+                    continue;
+                }
+                总数 += 1;
+                let 键 = 映射[*变体][0];
+                if !映射[*基础].iter().any(|x| *x != 0 && *x == 键) {
+                    不同键数 += 1;
+                }
+            }
+            let mismatch = 不同键数 as f64 / 总数.max(1) as f64;
+            损失函数 += mismatch * 权重;
+            指标.variant_mismatch = Some(mismatch);
+        }
+        if let Some(权重) = self.配置.key_entropy {
+            let mut 计数 = vec![0; 进制];
+            for 元素 in 进制..决策.元素.len() {
+                if 选取(元素) {
+                    计数[映射[元素][0] as usize] += 1;
+                }
+            }
+            let 总数: usize = 计数[1..].iter().sum();
+            let mut entropy = 0.0;
+            for x in 计数[1..].iter().filter(|x| **x > 0) {
+                let p = *x as f64 / 总数 as f64;
+                entropy -= p * p.log2();
+            }
+            损失函数 += entropy * 权重;
+            指标.key_entropy = Some(entropy);
+        }
+        (指标, 损失函数)
+    }
+}
@@ -1,15 +1,121 @@
 // 递归定义各种度量的数据结构以及它们输出到命令行的方式
 
+use super::learnability::易学性指标;
+use crate::config::{指法类别配置, 指法谓词, 滚动方向, 键盘布局配置};
+use crate::{指法向量, 错误};
 use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
 type 指法集 = FxHashSet<(char, char)>;
-type 键盘布局 = [Vec<char>; 4];
+
+#[derive(Debug, PartialEq, PartialOrd, Copy, Clone)]
+pub enum 手指 {
+    大拇指,
+    食指,
+    中指,
+    无名指,
+    小指,
+}
+
+/// 一个按键在键盘上的位置，列是按键在行内的序号，横坐标则考虑了行的错位
+#[derive(Debug, Clone, Copy)]
+pub struct 按键位置 {
+    pub 行: usize,
+    pub 列: usize,
+    pub 横坐标: f64,
+    pub 左手: bool,
+    pub 手指: 手指,
+}
+
+impl 按键位置 {
+    /// 与键盘布局配置中相同的手指编号，0 到 4 为左手的小指到拇指，5 到 9 为右手的拇指到小指
+    pub fn 手指编号(&self) -> usize {
+        let 序号 = self.手指 as usize;
+        if self.左手 {
+            4 - 序号
+        } else {
+            5 + 序号
+        }
+    }
+}
+
+/// 从配置中的键盘布局得到的每个按键的位置和手指
+#[derive(Debug, Clone)]
+pub struct 键盘几何 {
+    pub 按键: FxHashMap<char, 按键位置>,
+    pub 基准行: usize,
+}
+
+impl 键盘几何 {
+    pub fn 新建(配置: &键盘布局配置) -> Result<Self, 错误> {
+        use 手指::*;
+        if 配置.rows.len() != 配置.fingers.len() {
+            return Err("键盘布局中 rows 和 fingers 的行数不一致".into());
+        }
+        if let Some(错位) = &配置.offsets {
+            if 错位.len() != 配置.rows.len() {
+                return Err("键盘布局中 rows 和 offsets 的行数不一致".into());
+            }
+        }
+        let mut 按键 = FxHashMap::default();
+        for (行, (按键行, 手指行)) in 配置.rows.iter().zip(&配置.fingers).enumerate() {
+            let 错位 = 配置.offsets.as_ref().map_or(0.0, |x| x[行]);
+            if 按键行.chars().count() != 手指行.chars().count() {
+                return Err(format!("键盘布局第 {} 行的按键和手指数量不一致", 行 + 1).into());
+            }
+            for (列, (键, 手指编号)) in 按键行.chars().zip(手指行.chars()).enumerate() {
+                let 手指 = match 手指编号 {
+                    '0' | '9' => 小指,
+                    '1' | '8' => 无名指,
+                    '2' | '7' => 中指,
+                    '3' | '6' => 食指,
+                    '4' | '5' => 大拇指,
+                    _ => return Err(format!("键盘布局中的手指编号「{手指编号}」不正确").into()),
+                };
+                let 位置 = 按键位置 {
+                    行,
+                    列,
+                    横坐标: 列 as f64 + 错位,
+                    左手: 手指编号 < '5',
+                    手指,
+                };
+                if 按键.insert(键, 位置).is_some() {
+                    return Err(format!("键盘布局中的按键「{键}」重复").into());
+                }
+            }
+        }
+        Ok(Self {
+            按键,
+            基准行: 配置.home_row.unwrap_or(2),
+        })
+    }
+
+    /// 同一行中紧挨着的两个按键，以及相邻两行中横坐标相差小于一个键宽的两个按键视为相邻，不在键盘布局中的按键没有相邻的按键
+    pub fn 相邻(&self, 键一: char, 键二: char) -> bool {
+        match (self.按键.get(&键一), self.按键.get(&键二)) {
+            (Some(甲), Some(乙)) if 键一 != 键二 => {
+                let 横向距离 = (甲.横坐标 - 乙.横坐标).abs();
+                match 甲.行.abs_diff(乙.行) {
+                    0 => 甲.列.abs_diff(乙.列) == 1,
+                    1 => 横向距离 < 1.0,
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Default for 键盘几何 {
+    fn default() -> Self {
+        Self::新建(&键盘布局配置::default()).unwrap()
+    }
+}
 
 // 指法分析
 //
-// 参考法月的《科学形码测评系统》，基于定义来推导出各种差指法组合都有哪些，然后封装成一个结构体便于主程序使用。
+// 参考法月的《科学形码测评系统》，基于定义和键盘几何来推导出各种差指法组合都有哪些，然后封装成一个结构体便于主程序使用。
 #[derive(Debug, Default)]
 pub struct 指法标记 {
     pub 同手: 指法集,
@@ -19,89 +125,214 @@ pub struct 指法标记 {
     pub 错手: 指法集,
 }
 
-#[derive(PartialEq, PartialOrd, Copy, Clone)]
-enum 手指 {
-    _大拇指,
-    食指,
-    中指,
-    无名指,
-    小指,
-}
-
 impl 指法标记 {
-    pub fn new() -> 指法标记 {
-        let 左手: 键盘布局 = [
-            vec!['5', '4', '3', '2', '1'],
-            vec!['t', 'r', 'e', 'w', 'q'],
-            vec!['g', 'f', 'd', 's', 'a'],
-            vec!['b', 'v', 'c', 'x', 'z'],
-        ];
-        let 右手: 键盘布局 = [
-            vec!['6', '7', '8', '9', '0', '-', '='],
-            vec!['y', 'u', 'i', 'o', 'p', '[', ']'],
-            vec!['h', 'j', 'k', 'l', ';', '\''],
-            vec!['n', 'm', ',', '.', '/'],
-        ];
-        let mut 左手标记 = Self::生成单手指法标记(&左手);
-        let 右手标记 = Self::生成单手指法标记(&右手);
-        左手标记.同手.extend(右手标记.同手);
-        左手标记.同指大跨排.extend(右手标记.同指大跨排);
-        左手标记.同指小跨排.extend(右手标记.同指小跨排);
-        左手标记.小指干扰.extend(右手标记.小指干扰);
-        左手标记.错手.extend(右手标记.错手);
-        左手标记
-    }
-
-    fn 生成单手指法标记(单手布局: &键盘布局) -> 指法标记 {
+    pub fn new(键盘几何: &键盘几何) -> 指法标记 {
         use 手指::*;
-        let 列对应手指: [手指; 7] = [食指, 食指, 中指, 无名指, 小指, 小指, 小指];
         let 是长手指 = |x: 手指| x == 中指 || x == 无名指;
-        let mut 同手 = 指法集::default();
-        let mut 同指大跨排 = 指法集::default();
-        let mut 同指小跨排 = 指法集::default();
-        let mut 小指干扰 = 指法集::default();
-        let mut 错手 = 指法集::default();
-        for (行序号一, 行一) in 单手布局.iter().enumerate() {
-            for (行序号二, 行二) in 单手布局.iter().enumerate() {
-                for (列序号一, 列一) in 行一.iter().enumerate() {
-                    for (列序号二, 列二) in 行二.iter().enumerate() {
-                        let 组合 = (*列一, *列二);
-                        同手.insert(组合);
-                        let 手指一 = 列对应手指[列序号一];
-                        let 手指二 = 列对应手指[列序号二];
-                        let 行差值 = 行序号一.abs_diff(行序号二);
-                        let 列差值 = 列序号一.abs_diff(列序号二);
-                        if 手指一 == 手指二 {
-                            if 行差值 >= 2 {
-                                同指大跨排.insert(组合);
-                            } else if 行差值 == 1 || 列差值 == 1 {
-                                同指小跨排.insert(组合);
-                            }
-                        }
-                        if (手指一 == 小指 && 手指二 >= 中指)
-                            || (手指二 == 小指 && 手指一 >= 中指)
-                        {
-                            小指干扰.insert(组合);
-                        }
-                        let 错手一 = 是长手指(手指一) && 行序号一 > 行序号二 + 1;
-                        let 错手二 = 是长手指(手指二) && 行序号二 > 行序号一 + 1;
-                        if 错手一 || 错手二 {
-                            错手.insert(组合);
-                        }
+        let mut 标记 = 指法标记::default();
+        for (键一, 位置一) in &键盘几何.按键 {
+            for (键二, 位置二) in &键盘几何.按键 {
+                if 位置一.左手 != 位置二.左手 {
+                    continue;
+                }
+                let 组合 = (*键一, *键二);
+                标记.同手.insert(组合);
+                let 手指一 = 位置一.手指;
+                let 手指二 = 位置二.手指;
+                let 行差值 = 位置一.行.abs_diff(位置二.行);
+                let 列差值 = 位置一.列.abs_diff(位置二.列);
+                if 手指一 == 手指二 {
+                    if 行差值 >= 2 {
+                        标记.同指大跨排.insert(组合);
+                    } else if 行差值 == 1 || 列差值 == 1 {
+                        标记.同指小跨排.insert(组合);
                     }
                 }
+                if (手指一 == 小指 && 手指二 >= 中指) || (手指二 == 小指 && 手指一 >= 中指)
+                {
+                    标记.小指干扰.insert(组合);
+                }
+                let 错手一 = 是长手指(手指一) && 位置一.行 > 位置二.行 + 1;
+                let 错手二 = 是长手指(手指二) && 位置二.行 > 位置一.行 + 1;
+                if 错手一 || 错手二 {
+                    标记.错手.insert(组合);
+                }
             }
         }
-        指法标记 {
-            同手,
-            同指大跨排,
-            同指小跨排,
-            小指干扰,
-            错手,
+        标记
+    }
+
+    /// 统计一串按键中各类指法出现的次数，包括自定义指法类别
+    pub fn 计算指法向量(
+        &self, 按键: &[char], 自定义指法: &[自定义指法类别]
+    ) -> 指法向量 {
+        let mut 指法向量 = 指法向量::default();
+        self.遍历指法(按键, 自定义指法, |i| 指法向量[i] += 1);
+        指法向量
+    }
+
+    /// 与计算指法向量相同，但是累加到 u64 的计数上，用于语料这样任意长的按键序列
+    pub fn 累加指法计数(
+        &self,
+        按键: &[char],
+        自定义指法: &[自定义指法类别],
+        计数: &mut [u64; 8],
+    ) {
+        self.遍历指法(按键, 自定义指法, |i| 计数[i] += 1);
+    }
+
+    fn 遍历指法(
+        &self,
+        按键: &[char],
+        自定义指法: &[自定义指法类别],
+        mut 记录: impl FnMut(usize),
+    ) {
+        for pair in 按键.windows(2).map(|x| (x[0], x[1])) {
+            let 内置指法集 = [
+                &self.同手,
+                &self.同指大跨排,
+                &self.同指小跨排,
+                &self.小指干扰,
+                &self.错手,
+            ];
+            for (i, 指法集) in 内置指法集.iter().enumerate() {
+                if 指法集.contains(&pair) {
+                    记录(i);
+                }
+            }
+            for (j, 类别) in 自定义指法.iter().enumerate() {
+                if 类别.二元组合.contains(&pair) {
+                    记录(内置指法数量 + j);
+                }
+            }
+        }
+        for triple in 按键.windows(3).map(|x| (x[0], x[1], x[2])) {
+            if triple.0 == triple.1 && triple.1 == triple.2 {
+                记录(5);
+            }
+            for (j, 类别) in 自定义指法.iter().enumerate() {
+                if 类别.三元组合.contains(&triple) {
+                    记录(内置指法数量 + j);
+                }
+            }
+        }
+    }
+}
+
+/// 自定义指法类别，由配置中的按键组合和谓词展开得到
+#[derive(Debug, Clone, Default)]
+pub struct 自定义指法类别 {
+    pub 名称: String,
+    pub 二元组合: 指法集,
+    pub 三元组合: FxHashSet<(char, char, char)>,
+}
+
+impl 自定义指法类别 {
+    pub fn 新建(
+        配置: &指法类别配置, 键盘几何: &键盘几何
+    ) -> Result<Self, 错误> {
+        let mut 类别 = 自定义指法类别 {
+            名称: 配置.name.clone(),
+            ..Default::default()
+        };
+        for 组合 in 配置.ngrams.iter().flatten() {
+            let 按键: Vec<char> = 组合.chars().collect();
+            match 按键[..] {
+                [a, b] => {
+                    类别.二元组合.insert((a, b));
+                }
+                [a, b, c] => {
+                    类别.三元组合.insert((a, b, c));
+                }
+                _ => {
+                    return Err(format!(
+                        "指法类别「{}」中的组合「{组合}」必须包含两个或三个按键",
+                        配置.name
+                    )
+                    .into())
+                }
+            }
+        }
+        let Some(谓词) = &配置.predicate else {
+            return Ok(类别);
+        };
+        let mut 满足谓词 = 指法集::default();
+        for (键一, 位置一) in &键盘几何.按键 {
+            for (键二, 位置二) in &键盘几何.按键 {
+                if Self::判断(谓词, (*键一, 位置一), (*键二, 位置二)) {
+                    满足谓词.insert((*键一, *键二));
+                }
+            }
+        }
+        match 配置.length.unwrap_or(2) {
+            2 => 类别.二元组合.extend(满足谓词),
+            3 => {
+                for (a, b) in &满足谓词 {
+                    for (_, c) in 满足谓词.iter().filter(|(x, _)| x == b) {
+                        类别.三元组合.insert((*a, *b, *c));
+                    }
+                }
+            }
+            长度 => {
+                return Err(format!(
+                    "指法类别「{}」的长度 {长度} 不正确，只能为 2 或 3",
+                    配置.name
+                )
+                .into())
+            }
+        }
+        Ok(类别)
+    }
+
+    fn 判断(
+        谓词: &指法谓词,
+        (键一, 位置一): (char, &按键位置),
+        (键二, 位置二): (char, &按键位置),
+    ) -> bool {
+        let 同手 = 位置一.左手 == 位置二.左手;
+        let 同指 = 同手 && 位置一.手指 == 位置二.手指;
+        if 谓词.same_hand.is_some_and(|x| x != 同手)
+            || 谓词.same_finger.is_some_and(|x| x != 同指)
+            || 谓词.same_key.is_some_and(|x| x != (键一 == 键二))
+        {
+            return false;
+        }
+        if let Some(方向) = 谓词.direction {
+            // 手指按从拇指到小指的顺序排列，向内即是向拇指的方向
+            let 实际方向 = if 位置二.手指 < 位置一.手指 {
+                滚动方向::Inward
+            } else {
+                滚动方向::Outward
+            };
+            if !同手 || 同指 || 实际方向 != 方向 {
+                return false;
+            }
+        }
+        if let Some(最大行差) = 谓词.max_row_difference {
+            if 位置一.行.abs_diff(位置二.行) > 最大行差 {
+                return false;
+            }
         }
+        true
+    }
+
+    /// 从配置中构建所有的自定义指法类别
+    pub fn 构建列表(
+        配置: &Option<Vec<指法类别配置>>,
+        键盘几何: &键盘几何,
+    ) -> Result<Vec<Self>, 错误> {
+        let 配置列表 = 配置.as_deref().unwrap_or_default();
+        if 配置列表.len() > 自定义指法数量 {
+            return Err(format!("自定义指法类别最多只能有 {自定义指法数量} 个").into());
+        }
+        配置列表.iter().map(|x| Self::新建(x, 键盘几何)).collect()
     }
 }
 
+/// 指法向量中前六位为内置的指法类别，后两位留给自定义指法类别
+pub const 内置指法数量: usize = 6;
+pub const 自定义指法数量: usize = 2;
+
 const 指法标记名称: [&str; 8] = [
     "同手", "大跨", "小跨", "干扰", "错手", "三连", "备用", "备用",
 ];
@@ -124,9 +355,28 @@ pub type FingeringMetricUniform = [Option<u64>; 8];
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct 层级指标 {
     pub top: Option<usize>,
+    pub coverage: Option<f64>,
+    pub charset: Option<String>,
     pub duplication: Option<u64>,
     pub levels: Option<Vec<LevelMetricUniform>>,
     pub fingering: Option<FingeringMetricUniform>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fingering_labels: Vec<String>,
+    pub pair_equivalence: Option<f64>,
+    pub key_distribution: Option<FxHashMap<char, f64>>,
+    pub key_distribution_loss: Option<f64>,
+    pub finger_distribution: Option<Vec<f64>>,
+    pub finger_distribution_loss: Option<f64>,
+    pub alternation: Option<f64>,
+    pub home_row: Option<f64>,
+}
+
+/// 指法向量中第 index 位的名称，自定义指法类别使用用户给出的名称
+pub fn 指法名称(自定义名称: &[String], index: usize) -> &str {
+    index
+        .checked_sub(内置指法数量)
+        .and_then(|i| 自定义名称.get(i))
+        .map_or(指法标记名称[index], |x| x.as_str())
 }
 
 impl Display for 层级指标 {
@@ -134,12 +384,41 @@ impl Display for 层级指标 {
         let hanzi_numbers: Vec<char> = "一二三四五六七八九十".chars().collect();
         let specifier = if let Some(top) = self.top {
             format!("{top} ")
+        } else if let Some(coverage) = self.coverage {
+            format!("覆盖 {}% ", coverage * 100.0)
+        } else if let Some(charset) = &self.charset {
+            format!("{charset} ")
         } else {
             String::from("全部")
         };
         if let Some(duplication) = self.duplication {
             f.write_str(&format!("{specifier}选重：{duplication}；"))?;
         }
+        if let Some(equivalence) = self.pair_equivalence {
+            f.write_str(&format!("{specifier}组合当量：{equivalence:.4}；"))?;
+        }
+        if let Some(loss) = self.key_distribution_loss {
+            f.write_str(&format!("{specifier}用指分布偏差：{:.2}%；", loss * 100.0))?;
+        }
+        if let Some(loss) = self.finger_distribution_loss {
+            f.write_str(&format!("{specifier}手指分布偏差：{:.2}%；", loss * 100.0))?;
+        }
+        if let Some(distribution) = &self.finger_distribution {
+            let percents: Vec<_> = distribution
+                .iter()
+                .map(|x| format!("{:.2}%", x * 100.0))
+                .collect();
+            f.write_str(&format!("{specifier}手指分布：{}；", percents.join(" ")))?;
+        }
+        if let Some(alternation) = self.alternation {
+            f.write_str(&format!("{specifier}换手率：{:.2}%；", alternation * 100.0))?;
+        }
+        if let Some(home_row) = self.home_row {
+            f.write_str(&format!(
+                "{specifier}基准行使用率：{:.2}%；",
+                home_row * 100.0
+            ))?;
+        }
         if let Some(levels) = &self.levels {
             for LevelMetricUniform { length, frequency } in levels {
                 f.write_str(&format!(
@@ -151,7 +430,11 @@ impl Display for 层级指标 {
         if let Some(fingering) = &self.fingering {
             for (index, frequency) in fingering.iter().enumerate() {
                 if let Some(frequency) = frequency {
-                    f.write_str(&format!("{}：{}；", 指法标记名称[index], frequency))?;
+                    f.write_str(&format!(
+                        "{}：{}；",
+                        指法名称(&self.fingering_labels, index),
+                        frequency
+                    ))?;
                 }
             }
         }
@@ -165,10 +448,20 @@ pub struct 分组指标 {
     pub duplication: Option<f64>,
     pub key_distribution: Option<FxHashMap<char, f64>>,
     pub key_distribution_loss: Option<f64>,
+    pub finger_distribution: Option<Vec<f64>>,
+    pub finger_distribution_loss: Option<f64>,
+    pub hand_distribution: Option<Vec<f64>>,
+    pub hand_distribution_loss: Option<f64>,
     pub pair_equivalence: Option<f64>,
     pub extended_pair_equivalence: Option<f64>,
     pub fingering: Option<FingeringMetric>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fingering_labels: Vec<String>,
     pub levels: Option<Vec<键长指标>>,
+    pub selection: Option<f64>,
+    pub typing_time: Option<f64>,
+    pub alternation: Option<f64>,
+    pub home_row: Option<f64>,
 }
 
 pub const 键盘布局: [[char; 10]; 5] = [
@@ -185,24 +478,56 @@ impl Display for 分组指标 {
         if let Some(duplication) = self.duplication {
             f.write_str(&format!("选重率：{:.4}%；", duplication * 100.0))?;
         }
+        if let Some(selection) = self.selection {
+            f.write_str(&format!("平均选择键数：{:.4}；", selection))?;
+        }
+        if let Some(typing_time) = self.typing_time {
+            f.write_str(&format!("每字打字时间：{:.1} 毫秒；", typing_time))?;
+        }
         if let Some(key_distribution_loss) = self.key_distribution_loss {
             f.write_str(&format!(
                 "用指分布偏差：{:.2}%；",
                 key_distribution_loss * 100.0
             ))?;
         }
+        if let Some(loss) = self.finger_distribution_loss {
+            f.write_str(&format!("手指分布偏差：{:.2}%；", loss * 100.0))?;
+        }
+        if let Some(distribution) = &self.finger_distribution {
+            let percents: Vec<_> = distribution
+                .iter()
+                .map(|x| format!("{:.2}%", x * 100.0))
+                .collect();
+            f.write_str(&format!("手指分布：{}；", percents.join(" ")))?;
+        }
+        if let Some(loss) = self.hand_distribution_loss {
+            f.write_str(&format!("左右手分布偏差：{:.2}%；", loss * 100.0))?;
+        }
+        if let Some(distribution) = &self.hand_distribution {
+            f.write_str(&format!(
+                "左右手分布：{:.2}% {:.2}%；",
+                distribution[0] * 100.0,
+                distribution[1] * 100.0
+            ))?;
+        }
         if let Some(equivalence) = self.pair_equivalence {
             f.write_str(&format!("组合当量：{equivalence:.4}；"))?;
         }
         if let Some(equivalence) = self.extended_pair_equivalence {
             f.write_str(&format!("词间当量：{equivalence:.4}；"))?;
         }
+        if let Some(alternation) = self.alternation {
+            f.write_str(&format!("换手率：{:.2}%；", alternation * 100.0))?;
+        }
+        if let Some(home_row) = self.home_row {
+            f.write_str(&format!("基准行使用率：{:.2}%；", home_row * 100.0))?;
+        }
         if let Some(fingering) = &self.fingering {
             for (index, percent) in fingering.iter().enumerate() {
                 if let Some(percent) = percent {
                     f.write_str(&format!(
                         "{}：{:.2}%；",
-                        指法标记名称[index],
+                        指法名称(&self.fingering_labels, index),
                         percent * 100.0
                     ))?;
                 }
@@ -247,6 +572,30 @@ pub struct 默认指标 {
     pub words_full: Option<分组指标>,
     pub words_short: Option<分组指标>,
     pub complexity: Option<f64>,
+    pub learnability: Option<易学性指标>,
+    pub feasible: Option<bool>,
+    pub violation: Option<f64>,
+}
+
+impl 默认指标 {
+    /// 把自定义指法类别的名称附加到各个分组和层级的指标上
+    pub fn 附加指法名称(&mut self, 名称: &[String]) {
+        let 分组列表 = [
+            &mut self.characters_full,
+            &mut self.characters_short,
+            &mut self.words_full,
+            &mut self.words_short,
+        ];
+        for 分组 in 分组列表 {
+            let Some(分组) = 分组 else {
+                continue;
+            };
+            分组.fingering_labels = 名称.to_vec();
+            for 层级 in 分组.tiers.iter_mut().flatten() {
+                层级.fingering_labels = 名称.to_vec();
+            }
+        }
+    }
 }
 
 impl Display for 默认指标 {
@@ -254,6 +603,12 @@ impl Display for 默认指标 {
         if let Some(复杂度) = &self.complexity {
             f.write_str(&format!("复杂度：{复杂度:.2}；\n"))?;
         }
+        if let Some(易学性) = &self.learnability {
+            f.write_str(&format!("易学性［{易学性}］\n"))?;
+        }
+        if let Some(违反量) = self.violation {
+            f.write_str(&format!("约束违反量：{违反量:.4}；\n"))?;
+        }
         if let Some(characters) = &self.characters_full {
             f.write_str(&format!("一字全码［{characters}］\n"))?;
         }
@@ -293,10 +648,80 @@ mod tests {
             小指干扰,
             错手,
             ..
-        } = 指法标记::new();
+        } = 指法标记::new(&键盘几何::default());
         test_subset(同指大跨排, 测评系统同指大跨排);
         test_subset(同指小跨排, 测评系统同指小跨排);
         test_subset(小指干扰, 测评系统小指干扰);
         test_subset(错手, 测评系统错手);
     }
+
+    #[test]
+    fn test_custom_fingering_predicates() {
+        let 键盘几何 = 键盘几何::default();
+        let 配置: Vec<指法类别配置> = serde_yaml::from_str(
+            r#"
+- name: 内卷
+  predicate: {same_hand: true, direction: inward, max_row_difference: 0}
+- name: 异手三连
+  ngrams: [qp]
+  predicate: {same_hand: false}
+  length: 3
+"#,
+        )
+        .unwrap();
+        let 类别列表 = 自定义指法类别::构建列表(&Some(配置), &键盘几何).unwrap();
+        let [内卷, 异手三连] = &类别列表[..] else {
+            panic!("应当有两个类别");
+        };
+        // 同一行中从小指向食指滚动
+        test_subset(内卷.二元组合.clone(), "as sd df ;l lk kj");
+        for pair in ["sa", "fd", "ar", "aa", "fg", "aj"] {
+            let chars: Vec<char> = pair.chars().collect();
+            assert!(!内卷.二元组合.contains(&(chars[0], chars[1])), "{}", pair);
+        }
+        // 三元组合中相邻的两对都要满足谓词，直接给出的二元组合也计入
+        assert!(异手三连.三元组合.contains(&('a', 'j', 'f')));
+        assert!(!异手三连.三元组合.contains(&('a', 'j', 'k')));
+        assert!(异手三连.二元组合.contains(&('q', 'p')));
+        let 指法标记 = 指法标记::new(&键盘几何);
+        let 指法向量 = 指法标记.计算指法向量(&['a', 's', 'j', 'f'], &类别列表);
+        assert_eq!(指法向量[内置指法数量], 1);
+        assert_eq!(指法向量[内置指法数量 + 1], 1);
+        let 配置 = 指法类别配置 {
+            name: "错误".to_string(),
+            ngrams: Some(vec!["abcd".to_string()]),
+            predicate: None,
+            length: None,
+        };
+        assert!(自定义指法类别::新建(&配置, &键盘几何).is_err());
+    }
+
+    #[test]
+    fn test_adjacent_keys() {
+        let 键盘几何 = 键盘几何::default();
+        let 相邻按键: String = "qwertyuiopasdfghjklzxcvbnm"
+            .chars()
+            .filter(|x| 键盘几何.相邻('g', *x))
+            .collect();
+        // 默认布局考虑了行的错位，g 的上方是 t 和 y，下方是 v 和 b
+        assert_eq!(相邻按键, "tyfhvb");
+        assert!(键盘几何.相邻('q', '1'));
+        assert!(键盘几何.相邻('q', '2'));
+        assert!(!键盘几何.相邻('q', '3'));
+        assert!(!键盘几何.相邻('g', 'g'));
+        assert!(!键盘几何.相邻('g', '_'));
+        // 不给出错位时各行对齐，只看行和列
+        let mut 配置 = 键盘布局配置 {
+            offsets: None,
+            ..Default::default()
+        };
+        let 键盘几何 = 键盘几何::新建(&配置).unwrap();
+        let 相邻按键: String = "qwertyuiopasdfghjklzxcvbnm"
+            .chars()
+            .filter(|x| 键盘几何.相邻('g', *x))
+            .collect();
+        assert_eq!(相邻按键, "tfhb");
+        配置.offsets = Some(vec![0.0]);
+        assert!(键盘几何::新建(&配置).is_err());
+    }
 }
@@ -5,8 +5,12 @@
 use crate::optimizers::决策;
 use serde::Serialize;
 use std::fmt::Display;
+pub mod analysis;
 pub mod cache;
+pub mod corpus;
 pub mod default;
+pub mod expression;
+pub mod learnability;
 pub mod metric;
 
 pub trait 目标函数 {
@@ -17,4 +21,17 @@ pub trait 目标函数 {
         决策: &Self::决策,
         决策变化: &Option<<Self::决策 as 决策>::变化>,
     ) -> (Self::目标值, f64);
+
+    /// 目标值是否满足所有约束，没有约束的目标函数总是可行的
+    fn 可行(_目标值: &Self::目标值) -> bool {
+        true
+    }
+
+    /// 甲相对于乙的目标函数差值，负数表示甲更优。有约束的目标函数可以在比较时同时考虑约束的违反量
+    fn 差值(&self, 甲: &(Self::目标值, f64), 乙: &(Self::目标值, f64)) -> f64 {
+        甲.1 - 乙.1
+    }
+
+    /// 在输出目标值之前补上只用于展示的信息，例如自定义指法类别的名称。计算目标值时不生成这些信息，以免拖慢优化
+    fn 附加说明(&self, _目标值: &mut Self::目标值) {}
 }
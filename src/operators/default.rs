@@ -1,10 +1,11 @@
 use super::变异;
-use crate::contexts::default::{默认上下文, 默认决策, 默认决策空间};
+use crate::config::求解器配置;
+use crate::contexts::default::{默认上下文, 默认决策, 默认决策空间, 默认安排};
 use crate::optimizers::决策;
 use crate::错误;
 use crate::{元素, 元素图};
 use rand::seq::{IndexedRandom, IteratorRandom};
-use rand::{random_range, rng};
+use rand::{random, random_range, rng};
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use std::collections::VecDeque;
@@ -12,6 +13,7 @@ use std::collections::VecDeque;
 pub struct 默认操作 {
     决策空间: 默认决策空间,
     元素图: 元素图,
+    拆分变异概率: f64,
 }
 
 #[skip_serializing_none]
@@ -20,29 +22,63 @@ pub struct 变异配置 {
     pub random_move: f64,
     pub random_swap: f64,
     pub random_full_key_swap: f64,
+    /// 有多种拆分的词时，每次变异以这个概率改变一个词的拆分
+    #[serde(default = "变异配置::默认拆分变异概率")]
+    pub random_decomposition: f64,
+}
+
+impl 变异配置 {
+    const fn 默认拆分变异概率() -> f64 {
+        0.1
+    }
 }
 
 pub const DEFAULT_MUTATE: 变异配置 = 变异配置 {
     random_move: 0.9,
     random_swap: 0.09,
     random_full_key_swap: 0.01,
+    random_decomposition: 变异配置::默认拆分变异概率(),
 };
 
 impl 变异 for 默认操作 {
     type 决策 = 默认决策;
     fn 变异(&mut self, 决策: &mut Self::决策) -> <默认决策 as 决策>::变化 {
+        if !self.决策空间.拆分.is_empty() && random::<f64>() < self.拆分变异概率 {
+            return self.随机拆分(决策);
+        }
         let mut 变化 = self.随机移动(决策);
         self.传播(&mut 变化, 决策);
         变化
     }
 }
 
+/// 安排是否归并到或者引用了列表中的元素
+///
+/// 这样的元素安排本身不变，但是实际的编码取自被归并或被引用的元素，所以那个元素移动之后，它的编码也会随之改变。增量编码只重新计算变化列表中的元素所在的词，所以传播时要把它也加入变化列表
+fn 引用(安排: &默认安排, 元素列表: &[元素]) -> bool {
+    match 安排 {
+        默认安排::归并(元素) => 元素列表.contains(元素),
+        默认安排::键位(码位) => 码位.iter().any(|(元素, _)| 元素列表.contains(元素)),
+        默认安排::未选取 => false,
+    }
+}
+
 // 默认的问题实现，使用配置文件中的约束来定义各种算子
 impl 默认操作 {
     pub fn 新建(上下文: &默认上下文) -> Result<Self, 错误> {
+        let 求解器 = 上下文
+            .配置
+            .optimization
+            .as_ref()
+            .and_then(|x| x.metaheuristic.as_ref());
+        let 变异配置 = match 求解器 {
+            Some(求解器配置::SimulatedAnnealing(退火)) => 退火.search_method,
+            None => None,
+        };
         Ok(Self {
             决策空间: 上下文.决策空间.clone(),
             元素图: 上下文.元素图.clone(),
+            拆分变异概率: 变异配置.unwrap_or(DEFAULT_MUTATE).random_decomposition,
         })
     }
 
@@ -52,7 +88,7 @@ impl 默认操作 {
         for 元素 in 变化.iter() {
             for 下游元素 in self.元素图.get(元素).unwrap_or(&vec![]) {
                 if !队列.contains(下游元素) {
-                    队列.push_back(下游元素.clone());
+                    队列.push_back(*下游元素);
                 }
             }
         }
@@ -83,15 +119,37 @@ impl 默认操作 {
                     变化.push(元素);
                     决策.元素[元素] = 新安排.clone();
                 }
+            } else if !变化.contains(&元素) && 引用(&决策.元素[元素], 变化) {
+                // 安排本身没有变，但是归并或引用的元素变了，实际的编码也会随之改变
+                变化.push(元素);
             }
             for 下游元素 in self.元素图.get(&元素).unwrap_or(&vec![]) {
                 if !队列.contains(下游元素) {
-                    队列.push_back(下游元素.clone());
+                    队列.push_back(*下游元素);
                 }
             }
         }
     }
 
+    /// 随机选择一个词，换用它的另一种拆分。元素的安排不变，所以返回空的变化
+    pub fn 随机拆分(&self, 决策: &mut 默认决策) -> Vec<元素> {
+        let mut rng = rng();
+        let 可选 = self
+            .决策空间
+            .拆分
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.序列.len() > 1);
+        if let Some((序号, 候选拆分)) = 可选.choose(&mut rng) {
+            let 新拆分 = (0..候选拆分.序列.len())
+                .filter(|x| *x != 决策.拆分[序号])
+                .choose(&mut rng)
+                .unwrap();
+            决策.拆分[序号] = 新拆分;
+        }
+        vec![]
+    }
+
     pub fn 随机移动(&self, 决策: &mut 默认决策) -> Vec<元素> {
         let mut rng = rng();
         const MAX_TRIES: usize = 100;
@@ -101,7 +159,7 @@ impl 默认操作 {
             let mut 下一个安排 = None;
             let mut count = 0;
             for 条件安排 in &self.决策空间.元素[元素] {
-                if &条件安排.安排 != &决策.元素[元素] && 决策.允许(条件安排) {
+                if 条件安排.安排 != 决策.元素[元素] && 决策.允许(条件安排) {
                     count += 1;
                     if random_range(0..count) == 0 {
                         下一个安排 = Some(&条件安排.安排);
@@ -116,3 +174,118 @@ impl 默认操作 {
         vec![]
     }
 }
+
+/// 检验增量计算用的工具，编码器和目标函数的测试共用
+#[cfg(test)]
+pub(crate) mod 测试工具 {
+    use super::*;
+
+    /// 线性同余伪随机数，序列固定，用于生成可以复现的词表
+    pub struct 伪随机(pub u64);
+
+    impl 伪随机 {
+        pub fn 下一个(&mut self) -> usize {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (self.0 >> 33) as usize
+        }
+
+        /// 从给定的元素中随机选取，长度在最短和最长之间，包含两端
+        pub fn 元素序列(&mut self, 元素: &[&str], 最短: usize, 最长: usize) -> String {
+            let 长度 = 最短 + self.下一个() % (最长 - 最短 + 1);
+            let 序列: Vec<_> = (0..长度)
+                .map(|_| 元素[self.下一个() % 元素.len()])
+                .collect();
+            序列.join(" ")
+        }
+    }
+
+    /// 从初始决策开始随机变异，把每一步尝试的决策和相对于上一次计算的变化交给检查函数
+    ///
+    /// 隔一步拒绝一次，被拒绝时变化会累积到下一次，和模拟退火中一样
+    pub fn 逐步变异(
+        上下文: &默认上下文,
+        步数: usize,
+        mut 检查: impl FnMut(usize, &默认决策, Vec<元素>),
+    ) {
+        let mut 操作 = 默认操作::新建(上下文).unwrap();
+        let mut 当前决策 = 上下文.初始决策.clone();
+        let mut 上一个变化: Option<Vec<元素>> = None;
+        for 步骤 in 0..步数 {
+            let mut 尝试决策 = 当前决策.clone();
+            let 决策变化 = 操作.变异(&mut 尝试决策);
+            let 变化 = match &上一个变化 {
+                Some(上一个变化) => 默认决策::除法(上一个变化, &决策变化),
+                None => 决策变化.clone(),
+            };
+            检查(步骤, &尝试决策, 变化);
+            if 步骤 % 2 == 0 {
+                当前决策 = 尝试决策;
+                上一个变化 = None;
+            } else {
+                上一个变化 = Some(决策变化);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoders::{default::默认编码器, 编码器};
+    use crate::interfaces::默认输入;
+    use crate::{原始可编码对象, 编码信息};
+
+    const 测试配置: &str = r#"
+form:
+  alphabet: qwh
+  mapping: {口: q, 日: w, 人: {element: 口}}
+  mapping_space:
+    口: [{value: q, score: 0}, {value: h, score: 0}]
+    人: [{value: {element: 口}, score: 0}]
+encoder:
+  max_length: 2
+"#;
+
+    #[test]
+    fn test_propagate_to_merged_element() {
+        let 词列表 = [("一", "人 日"), ("二", "口 日")]
+            .iter()
+            .map(|(词, 元素序列)| 原始可编码对象 {
+                词: 词.to_string(),
+                元素序列: 元素序列.to_string(),
+                频率: 1,
+                简码长度: u64::MAX,
+                固定排名: None,
+            })
+            .collect();
+        let 上下文 = 默认上下文::新建(默认输入 {
+            配置: serde_yaml::from_str(测试配置).unwrap(),
+            词列表,
+            ..Default::default()
+        })
+        .unwrap();
+        let 操作 = 默认操作::新建(&上下文).unwrap();
+        let 口 = 上下文.棱镜.元素转数字["口"];
+        let 人 = 上下文.棱镜.元素转数字["人"];
+        // 把口从 q 移到 h，人归并到口，安排没有变，但是编码变了，所以也要出现在变化列表中
+        let mut 决策 = 上下文.初始决策.clone();
+        let 新安排 = 操作.决策空间.元素[口]
+            .iter()
+            .find(|x| x.安排 != 决策.元素[口])
+            .unwrap();
+        决策.元素[口] = 新安排.安排.clone();
+        let mut 变化 = vec![口];
+        操作.传播(&mut 变化, &mut 决策);
+        assert_eq!(变化, [口, 人]);
+        // 按照变化列表增量编码的结果与从头编码的相同
+        let mut 增量编码器 = 默认编码器::新建(&上下文).unwrap();
+        let mut 全量编码器 = 默认编码器::新建(&上下文).unwrap();
+        let mut 增量结果: Vec<_> = 上下文.词列表.iter().map(编码信息::new).collect();
+        let mut 全量结果 = 增量结果.clone();
+        增量编码器.编码(&上下文.初始决策, &None, &mut 增量结果);
+        增量编码器.编码(&决策, &Some(变化), &mut 增量结果);
+        全量编码器.编码(&决策, &None, &mut 全量结果);
+        let 全码 = |x: &[编码信息]| x.iter().map(|x| x.全码.实际编码).collect::<Vec<_>>();
+        assert_eq!(全码(&增量结果), 全码(&全量结果));
+    }
+}
@@ -27,6 +27,13 @@ pub struct 退火方法 {
     pub update_interval: Option<usize>,
 }
 
+/// 向界面报告的目标值，附加上只用于展示的信息
+fn 描述<O: 目标函数>(目标函数: &O, 目标值: &O::目标值) -> String {
+    let mut 目标值 = 目标值.clone();
+    目标函数.附加说明(&mut 目标值);
+    format!("{}", 目标值)
+}
+
 impl 退火方法 {
     pub fn 优化<O: 目标函数, F: 变异<决策 = O::决策>, C: 上下文<决策 = O::决策>>(
         &self,
@@ -54,6 +61,7 @@ impl 退火方法 {
     ) -> 优化结果<O> {
         let mut 最优决策 = 初始决策.clone();
         let mut 最优指标 = 目标函数.计算(&最优决策, &None);
+        let mut 最优可行 = O::可行(&最优指标.0);
         let mut 当前决策 = 最优决策.clone();
         let mut 当前指标 = 最优指标.clone();
         let 降温时间表 {
@@ -74,7 +82,8 @@ impl 退火方法 {
                 界面.发送(消息::Progress {
                     steps: 步骤,
                     temperature: 温度,
-                    metric: format!("{}", 当前指标.0),
+                    metric: 描述(目标函数, &当前指标.0),
+                    feasible: O::可行(&当前指标.0),
                 });
                 if 步骤 == 更新频率 {
                     let elapsed = 开始时间.elapsed().as_micros() as u64 / 更新频率 as u64;
@@ -91,7 +100,7 @@ impl 退火方法 {
             };
             let 尝试指标 = 目标函数.计算(&尝试决策, &Some(变化));
             // 如果满足退火条件，接受新决策
-            let 改进 = 尝试指标.1 - 当前指标.1;
+            let 改进 = 目标函数.差值(&尝试指标, &当前指标);
             if 改进 < 0.0 || (rand::random::<f64>() < (-改进 / 温度).exp()) {
                 当前决策.clone_from(&尝试决策);
                 当前指标 = 尝试指标;
@@ -99,27 +108,38 @@ impl 退火方法 {
             } else {
                 上一个变化 = Some(决策变化);
             }
-            // 如果当前决策优于目前的最优决策，更新最优决策
-            if 当前指标.1 < 最优指标.1 {
+            // 如果当前决策优于目前的最优决策，更新最优决策。一旦找到了可行的决策，就只考虑可行的决策
+            let 当前可行 = O::可行(&当前指标.0);
+            let 更优 = if 当前可行 == 最优可行 {
+                目标函数.差值(&当前指标, &最优指标) < 0.0
+            } else {
+                当前可行
+            };
+            if 更优 {
                 最优指标 = 当前指标.clone();
                 最优决策.clone_from(&当前决策);
+                最优可行 = 当前可行;
                 let 是否保存 = 进度 > self.report_after.unwrap_or(0.9);
                 界面.发送(消息::BetterSolution {
-                    metric: format!("{}", 最优指标.0),
+                    metric: 描述(目标函数, &最优指标.0),
                     config: 上下文.序列化(&最优决策),
                     save: 是否保存,
+                    feasible: 最优可行,
                 })
             }
         }
         界面.发送(消息::BetterSolution {
-            metric: format!("{}", 最优指标.0),
+            metric: 描述(目标函数, &最优指标.0),
             config: 上下文.序列化(&最优决策),
             save: true,
+            feasible: 最优可行,
         });
+        let (mut 指标, 分数) = 最优指标;
+        目标函数.附加说明(&mut 指标);
         优化结果 {
             映射: 最优决策,
-            指标: 最优指标.0.clone(),
-            分数: 最优指标.1,
+            指标,
+            分数,
         }
     }
 
@@ -132,15 +152,15 @@ impl 退火方法 {
         steps: usize,
     ) -> (O::决策, f64, f64) {
         let mut candidate = from.clone();
-        let (_, mut energy) = 目标函数.计算(&candidate, &None);
+        let mut energy = 目标函数.计算(&candidate, &None);
         let mut accepts = 0;
         let mut improves = 0;
 
         for _ in 0..steps {
             let mut next_candidate = candidate.clone();
             let moved_elements = 操作.变异(&mut next_candidate);
-            let (_, next_energy) = 目标函数.计算(&next_candidate, &Some(moved_elements));
-            let energy_delta = next_energy - energy;
+            let next_energy = 目标函数.计算(&next_candidate, &Some(moved_elements));
+            let energy_delta = 目标函数.差值(&next_energy, &energy);
             if energy_delta < 0.0 || (-energy_delta / temperature).exp() > rand::random::<f64>() {
                 accepts += 1;
                 if energy_delta < 0.0 {
@@ -172,13 +192,13 @@ impl 退火方法 {
 
         let batch = 1000;
         let mut candidate = 初始决策.clone();
-        let (_, energy) = 目标函数.计算(&candidate, &None);
+        let energy = 目标函数.计算(&candidate, &None);
         let mut sum_delta = 0.0;
         for _ in 0..batch {
             let mut next_candidate = candidate.clone();
             let moved_elements = 操作.变异(&mut next_candidate);
-            let (_, next_energy) = 目标函数.计算(&next_candidate, &Some(moved_elements));
-            sum_delta += (next_energy - energy).abs();
+            let next_energy = 目标函数.计算(&next_candidate, &Some(moved_elements));
+            sum_delta += 目标函数.差值(&next_energy, &energy).abs();
         }
         let initial_guess = sum_delta / batch as f64;
         let mut temperature = initial_guess;
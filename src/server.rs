@@ -137,6 +137,32 @@ pub async fn encode_evaluate(
     }
 }
 
+/// HTTP API: 贡献分析
+pub async fn analyze(
+    State(state): State<AppState>,
+    Json(objective): Json<serde_json::Value>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    info!("POST /api/analyze");
+
+    match serde_json::from_value::<目标配置>(objective) {
+        Ok(目标函数配置) => {
+            let api = state.api.read().await;
+            let result = api.analyze(目标函数配置);
+            drop(api);
+
+            match result {
+                Ok(result) => Json(ApiResponse::Success {
+                    result: serde_json::json!(result),
+                }),
+                Err(e) => Json(ApiResponse::Error { error: e.message }),
+            }
+        }
+        Err(e) => Json(ApiResponse::Error {
+            error: format!("目标函数配置解析错误: {e}"),
+        }),
+    }
+}
+
 /// HTTP API: 开始优化（异步）
 pub async fn start_optimize(State(state): State<AppState>) -> Json<ApiResponse<String>> {
     info!("POST /api/optimize");
@@ -285,6 +311,7 @@ pub async fn index() -> Html<&'static str> {
         <li><code>POST /api/validate</code> - 验证配置</li>
         <li><code>POST /api/sync</code> - 同步参数</li>
         <li><code>POST /api/encode</code> - 编码评估</li>
+        <li><code>POST /api/analyze</code> - 贡献分析</li>
         <li><code>POST /api/optimize</code> - 开始优化</li>
         <li><code>GET /sse/status</code> - SSE 实时状态推送</li>
     </ul>
@@ -519,10 +546,8 @@ pub fn create_app() -> Router {
             
             // 只在重要进度时记录
             match 消息 {
-                crate::interfaces::消息::Progress { steps, .. } => {
-                    if steps % 100 == 0 {
-                        info!("[CALLBACK] 优化进度: {} 步", steps);
-                    }
+                crate::interfaces::消息::Progress { steps, .. } if steps % 100 == 0 => {
+                    info!("[CALLBACK] 优化进度: {} 步", steps);
                 }
                 crate::interfaces::消息::BetterSolution { .. } => {
                     info!("[CALLBACK] 发现更优解");
@@ -556,6 +581,7 @@ pub fn create_app() -> Router {
         .route("/api/validate", post(validate_config))
         .route("/api/sync", post(sync_params))
         .route("/api/encode", post(encode_evaluate))
+        .route("/api/analyze", post(analyze))
         .route("/api/optimize", post(start_optimize))
         .route("/sse/status", get(sse_handler))
         .fallback_service(ServeDir::new("client"))